@@ -1,5 +1,37 @@
-use log::info;
+use log::{info, log, log_enabled, Level, LevelFilter};
 
-pub fn log() {
-    info!("logged using log@0.4.22");
+include!("../../shared/provenance.rs");
+
+provenance!(max_level: log::max_level(), error: Level::Error);
+
+pub fn log() -> Provenance {
+    info!("logged using log@{LOG_VERSION}");
+    provenance()
+}
+
+/// The level `log()` records at, as this crate's `log::Level`.
+pub fn level() -> Level {
+    Level::Info
+}
+
+/// Sets the global max level of this crate's copy of `log`. Only a
+/// `LevelFilter` from log 0.4 is accepted.
+pub fn set_max_level(filter: LevelFilter) {
+    log::set_max_level(filter);
+}
+
+/// Logs `message` at `level`. Only a `Level` from log 0.4 is accepted.
+pub fn log_at(level: Level, message: &str) {
+    log!(level, "{message}");
+}
+
+/// Registers `name` with the copy of `plugins` this crate was built against
+/// (1.0.0).
+pub fn register_plugin(name: &'static str) {
+    plugins::register(name);
+}
+
+/// The plugins registered with this crate's copy of `plugins`.
+pub fn plugins() -> Vec<&'static str> {
+    plugins::registered()
 }
@@ -3,7 +3,40 @@
 extern crate log;
 
 use log::info;
+use log::{LogLevel, LogLevelFilter};
 
-pub fn log() {
-    info!("logged using log@0.3.9");
+include!("../../shared/provenance.rs");
+
+// log 0.3 calls it `max_log_level` and `LogLevelFilter`
+provenance!(max_level: log::max_log_level(), error: LogLevel::Error);
+
+pub fn log() -> Provenance {
+    info!("logged using log@{}", LOG_VERSION);
+    provenance()
+}
+
+/// The level `log()` records at, as this crate's `log::LogLevel`.
+pub fn level() -> LogLevel {
+    LogLevel::Info
+}
+
+/// The global max level as this crate's copy of `log` sees it.
+pub fn max_level() -> LogLevelFilter {
+    log::max_log_level()
+}
+
+/// Logs `message` at `level`. Only a `LogLevel` from log 0.3.9 is accepted.
+pub fn log_at(level: LogLevel, message: &str) {
+    log!(level, "{}", message);
+}
+
+/// Registers `name` with the copy of `plugins` this crate was built against
+/// (2.0.0).
+pub fn register_plugin(name: &'static str) {
+    plugins::register(name);
+}
+
+/// The plugins registered with this crate's copy of `plugins`.
+pub fn plugins() -> Vec<&'static str> {
+    plugins::registered()
 }
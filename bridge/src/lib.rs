@@ -0,0 +1,84 @@
+//! Converts between the types of log 0.3 and log 0.4.
+//!
+//! Neither crate can implement `From` for the other's types, and orphan rules
+//! keep this crate from doing it either, so the conversions are plain
+//! functions named after the direction they go in.
+//!
+//! log 0.3 keeps the fields of `LogRecord` and `LogMetadata` private, so those
+//! only convert upwards. A log 0.4 `Record` can still be sent the other way
+//! with [`log_to_03`], which re-emits it through log 0.3's macros.
+
+use log03::{LogLevel, LogLevelFilter, LogMetadata, LogRecord};
+use log04::{Level, LevelFilter, Metadata, Record};
+
+pub fn level_to_04(level: LogLevel) -> Level {
+    match level {
+        LogLevel::Error => Level::Error,
+        LogLevel::Warn => Level::Warn,
+        LogLevel::Info => Level::Info,
+        LogLevel::Debug => Level::Debug,
+        LogLevel::Trace => Level::Trace,
+    }
+}
+
+pub fn level_to_03(level: Level) -> LogLevel {
+    match level {
+        Level::Error => LogLevel::Error,
+        Level::Warn => LogLevel::Warn,
+        Level::Info => LogLevel::Info,
+        Level::Debug => LogLevel::Debug,
+        Level::Trace => LogLevel::Trace,
+    }
+}
+
+pub fn level_filter_to_04(filter: LogLevelFilter) -> LevelFilter {
+    match filter {
+        LogLevelFilter::Off => LevelFilter::Off,
+        LogLevelFilter::Error => LevelFilter::Error,
+        LogLevelFilter::Warn => LevelFilter::Warn,
+        LogLevelFilter::Info => LevelFilter::Info,
+        LogLevelFilter::Debug => LevelFilter::Debug,
+        LogLevelFilter::Trace => LevelFilter::Trace,
+    }
+}
+
+pub fn level_filter_to_03(filter: LevelFilter) -> LogLevelFilter {
+    match filter {
+        LevelFilter::Off => LogLevelFilter::Off,
+        LevelFilter::Error => LogLevelFilter::Error,
+        LevelFilter::Warn => LogLevelFilter::Warn,
+        LevelFilter::Info => LogLevelFilter::Info,
+        LevelFilter::Debug => LogLevelFilter::Debug,
+        LevelFilter::Trace => LogLevelFilter::Trace,
+    }
+}
+
+pub fn metadata_to_04<'a>(metadata: &'a LogMetadata) -> Metadata<'a> {
+    Metadata::builder()
+        .level(level_to_04(metadata.level()))
+        .target(metadata.target())
+        .build()
+}
+
+pub fn record_to_04<'a>(record: &'a LogRecord) -> Record<'a> {
+    let location = record.location();
+    Record::builder()
+        .args(*record.args())
+        .metadata(metadata_to_04(record.metadata()))
+        .module_path(Some(location.module_path()))
+        .file(Some(location.file()))
+        .line(Some(location.line()))
+        .build()
+}
+
+/// Sends a log 0.4 record to log 0.3's logger with the same level, target and
+/// message. The file and line become this function's, because log 0.3 only
+/// accepts `'static` locations from its own macros.
+pub fn log_to_03(record: &Record) {
+    log03::log!(
+        target: record.target(),
+        level_to_03(record.level()),
+        "{}",
+        record.args()
+    );
+}
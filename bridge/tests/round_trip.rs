@@ -0,0 +1,120 @@
+use std::cmp::Ordering;
+use std::sync::{Mutex, Once};
+
+use bridge::{
+    level_filter_to_03, level_filter_to_04, level_to_03, level_to_04, log_to_03, record_to_04,
+};
+use log03::{LogLevel, LogLevelFilter, LogMetadata, LogRecord};
+use log04::{Level, LevelFilter, Record};
+
+const LEVELS_03: [LogLevel; 5] = [
+    LogLevel::Error,
+    LogLevel::Warn,
+    LogLevel::Info,
+    LogLevel::Debug,
+    LogLevel::Trace,
+];
+
+const FILTERS_03: [LogLevelFilter; 6] = [
+    LogLevelFilter::Off,
+    LogLevelFilter::Error,
+    LogLevelFilter::Warn,
+    LogLevelFilter::Info,
+    LogLevelFilter::Debug,
+    LogLevelFilter::Trace,
+];
+
+#[test]
+fn levels_round_trip() {
+    for level in LEVELS_03 {
+        assert_eq!(level_to_03(level_to_04(level)), level);
+    }
+    for level in Level::iter() {
+        assert_eq!(level_to_04(level_to_03(level)), level);
+    }
+}
+
+#[test]
+fn level_filters_round_trip() {
+    for filter in FILTERS_03 {
+        assert_eq!(level_filter_to_03(level_filter_to_04(filter)), filter);
+    }
+    for filter in LevelFilter::iter() {
+        assert_eq!(level_filter_to_04(level_filter_to_03(filter)), filter);
+    }
+}
+
+#[test]
+fn conversions_preserve_ordering() {
+    for a in LEVELS_03 {
+        for b in LEVELS_03 {
+            assert_eq!(a.cmp(&b), level_to_04(a).cmp(&level_to_04(b)));
+        }
+        for filter in FILTERS_03 {
+            let before: Option<Ordering> = a.partial_cmp(&filter);
+            let after = level_to_04(a).partial_cmp(&level_filter_to_04(filter));
+            assert_eq!(before, after, "{a} vs {filter}");
+        }
+    }
+}
+
+#[test]
+fn level_names_match() {
+    for level in LEVELS_03 {
+        assert_eq!(level.to_string(), level_to_04(level).to_string());
+    }
+    for filter in FILTERS_03 {
+        assert_eq!(filter.to_string(), level_filter_to_04(filter).to_string());
+    }
+}
+
+// Everything log 0.3 hands to its logger, converted back to log 0.4 terms.
+static CAPTURED: Mutex<Vec<(Level, String, String)>> = Mutex::new(Vec::new());
+
+struct Capture;
+
+impl log03::Log for Capture {
+    fn enabled(&self, _: &LogMetadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &LogRecord) {
+        let record = record_to_04(record);
+        CAPTURED.lock().unwrap().push((
+            record.level(),
+            record.target().to_string(),
+            record.args().to_string(),
+        ));
+    }
+}
+
+#[test]
+fn records_round_trip_through_log_03() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        log03::set_logger(|max_level| {
+            max_level.set(LogLevelFilter::Trace);
+            Box::new(Capture)
+        })
+        .unwrap();
+    });
+
+    let targets = ["bridge", "a::nested", "", "target with spaces"];
+    let messages = ["", "hello", "multi\nline", "unicode ✓"];
+    let mut expected = Vec::new();
+    for level in Level::iter() {
+        for target in targets {
+            for message in messages {
+                log_to_03(
+                    &Record::builder()
+                        .level(level)
+                        .target(target)
+                        .args(format_args!("{message}"))
+                        .build(),
+                );
+                expected.push((level, target.to_string(), message.to_string()));
+            }
+        }
+    }
+    assert_eq!(*CAPTURED.lock().unwrap(), expected);
+}
@@ -0,0 +1,78 @@
+// The build script of `a`, `b` and `c`. It looks up which version of `log`
+// Cargo resolved for the crate being built and exposes it as
+// `LOG_CRATE_VERSION`. The `log` crate has no version constant of its own.
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::ExitCode;
+
+use toml::{Table, Value};
+
+fn main() -> ExitCode {
+    match log_version() {
+        Ok(version) => {
+            println!("cargo:rustc-env=LOG_CRATE_VERSION={version}");
+            ExitCode::SUCCESS
+        }
+        Err(message) => {
+            eprintln!("error: {message}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn log_version() -> Result<String, String> {
+    let var = |key: &str| env::var(key).map_err(|e| format!("{key}: {e}"));
+    let name = var("CARGO_PKG_NAME")?;
+    let version = var("CARGO_PKG_VERSION")?;
+    // Cargo writes the lockfile next to the workspace root's manifest, in the
+    // crate's own directory or one above it.
+    let manifest_dir = var("CARGO_MANIFEST_DIR")?;
+    let path = Path::new(&manifest_dir)
+        .ancestors()
+        .map(|dir| dir.join("Cargo.lock"))
+        .find(|path| path.is_file())
+        .ok_or_else(|| format!("no Cargo.lock in {manifest_dir} or above it"))?;
+    println!("cargo:rerun-if-changed={}", path.display());
+    let contents = fs::read_to_string(&path)
+        .map_err(|e| format!("cannot read the workspace lockfile {}: {e}", path.display()))?;
+    let lockfile: Table = contents
+        .parse()
+        .map_err(|e| format!("cannot parse {}: {e}", path.display()))?;
+    resolved_version(&lockfile, &name, &version, "log").ok_or_else(|| {
+        format!(
+            "{} has no `log` dependency of {name} {version}",
+            path.display()
+        )
+    })
+}
+
+// Finds `dependency` in the `[[package]]` entry for `name` `version`. The
+// entry is `"log"` when only one `log` is locked, and `"log 0.4.22"` or
+// `"log 0.4.22 (source)"` otherwise.
+fn resolved_version(
+    lockfile: &Table,
+    name: &str,
+    version: &str,
+    dependency: &str,
+) -> Option<String> {
+    let packages = lockfile.get("package")?.as_array()?;
+    let field = |package: &Value, key: &str| package.get(key)?.as_str().map(String::from);
+    let package = packages.iter().find(|package| {
+        field(package, "name").as_deref() == Some(name)
+            && field(package, "version").as_deref() == Some(version)
+    })?;
+    let entry = package
+        .get("dependencies")?
+        .as_array()?
+        .iter()
+        .filter_map(Value::as_str)
+        .find(|entry| entry.split(' ').next() == Some(dependency))?;
+    match entry.split(' ').nth(1) {
+        Some(version) => Some(version.to_string()),
+        None => packages
+            .iter()
+            .find(|package| field(package, "name").as_deref() == Some(dependency))
+            .and_then(|package| field(package, "version")),
+    }
+}
@@ -0,0 +1,28 @@
+// Required for the outdated 0.3.* version of log
+#[macro_use]
+extern crate log;
+
+use log::info;
+use log::LogLevel;
+
+include!("../../shared/provenance.rs");
+
+// log 0.3 calls it `max_log_level` and `LogLevelFilter`
+provenance!(max_level: log::max_log_level(), error: LogLevel::Error);
+
+// Unlike b's log 0.3.9, log 0.3.8 has its own global logger and max level, so
+// this record is dropped unless something calls log 0.3.8's `set_logger`.
+pub fn log() -> Provenance {
+    info!("logged using log@{}", LOG_VERSION);
+    provenance()
+}
+
+/// The level `log()` records at, as this crate's `log::LogLevel`.
+pub fn level() -> LogLevel {
+    LogLevel::Info
+}
+
+/// Logs `message` at `level`. Only a `LogLevel` from log 0.3.8 is accepted.
+pub fn log_at(level: LogLevel, message: &str) {
+    log!(level, "{}", message);
+}
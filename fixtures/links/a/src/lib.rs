@@ -0,0 +1,3 @@
+pub fn greet_sys_version() -> &'static str {
+    greet_sys::VERSION
+}
@@ -0,0 +1,4 @@
+// A real `-sys` crate would find or build the native `greet` library here
+// and tell Cargo to link it. Cargo refuses to resolve two packages that
+// claim the same `links` name, so this never runs in the fixture.
+fn main() {}
@@ -0,0 +1,4 @@
+//! Bindings to the native `greet` library, which a binary can only link
+//! once.
+
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -0,0 +1,4 @@
+fn main() {
+    println!("a uses greet-sys {}", a::greet_sys_version());
+    println!("b uses greet-sys {}", b::greet_sys_version());
+}
@@ -0,0 +1,16 @@
+//! A process-wide plugin registry, the kind of global state that is silently
+//! split in two when a binary links two incompatible versions of a crate.
+
+use std::sync::Mutex;
+
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+static REGISTERED: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+
+pub fn register(name: &'static str) {
+    REGISTERED.lock().unwrap().push(name);
+}
+
+pub fn registered() -> Vec<&'static str> {
+    REGISTERED.lock().unwrap().clone()
+}
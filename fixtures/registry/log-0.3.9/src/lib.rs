@@ -0,0 +1,9 @@
+//! A stand-in for the real `log` 0.3.9, which forwards to log 0.4: the
+//! SemVer trick.
+
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// The version of log 0.4 this release forwards to.
+pub fn forwards_to() -> &'static str {
+    log::VERSION
+}
@@ -0,0 +1,3 @@
+//! A stand-in for the real `log`, published to the fixture registry.
+
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
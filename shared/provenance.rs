@@ -0,0 +1,34 @@
+// Included by the `lib.rs` of `a`, `b` and `c`, which report the same things
+// about their own copy of `log`. Only the calls differ between log 0.3 and
+// 0.4, so each crate passes in its own.
+
+/// Defines `LOG_VERSION`, `Provenance` and `provenance()`, given how this
+/// crate's `log` reads the global max level and names the error level.
+macro_rules! provenance {
+    (max_level: $max_level:expr, error: $error:expr) => {
+        /// The version of `log` Cargo resolved for this crate, read from
+        /// `Cargo.lock` by the build script.
+        pub const LOG_VERSION: &str = env!("LOG_CRATE_VERSION");
+
+        /// What this crate's copy of `log` reports about the logging setup.
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub struct Provenance {
+            pub crate_name: &'static str,
+            pub log_version: &'static str,
+            /// The global max level as seen by this crate's copy of `log`.
+            pub max_level: String,
+            /// Whether an installed logger accepts error records from this
+            /// crate.
+            pub logger_installed: bool,
+        }
+
+        pub fn provenance() -> Provenance {
+            Provenance {
+                crate_name: env!("CARGO_PKG_NAME"),
+                log_version: LOG_VERSION,
+                max_level: $max_level.to_string(),
+                logger_installed: log_enabled!($error),
+            }
+        }
+    };
+}
@@ -0,0 +1,224 @@
+//! Works out which packages hold a duplicated crate back on an older version,
+//! and the manifest edit that would move each of them to the newest one. Only
+//! the lockfile and the local manifests are consulted.
+
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+
+use crate::duplicates;
+use crate::graph::Graph;
+use crate::lockfile::Package;
+use crate::manifest::{self, Dependency, Manifest};
+use crate::semver::{self, VersionReq};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Advice<'a> {
+    pub name: &'a str,
+    /// The version everything else should move to: the newest one.
+    pub target: &'a Package,
+    /// Every older copy, lowest version first.
+    pub outliers: Vec<Outlier<'a>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Outlier<'a> {
+    pub package: &'a Package,
+    pub required_by: Vec<Requirer<'a>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Requirer<'a> {
+    /// A local package: its manifest can be edited. `changes` is empty if
+    /// the requirement couldn't be found, e.g. because it is inherited from
+    /// `[workspace.dependencies]`.
+    Local {
+        package: &'a Package,
+        manifest: PathBuf,
+        changes: Vec<Change>,
+    },
+    /// A registry or git package, which needs a newer release of its own.
+    /// `used_by` lists the local packages that pull it in.
+    Upstream {
+        package: &'a Package,
+        used_by: Vec<&'a Package>,
+    },
+}
+
+/// One dependency entry rewritten to accept the target version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    pub section: String,
+    pub before: String,
+    pub after: String,
+}
+
+/// Returns advice for every duplicated package in the graph's lockfile,
+/// sorted by name. `manifests` are the workspace's local manifests, as read
+/// by [`manifest::workspace`]. No package is advised to give up a copy it
+/// depends on [intentionally](Graph::is_intentional).
+pub fn advise<'a>(graph: &Graph<'a>, manifests: &[Manifest]) -> Vec<Advice<'a>> {
+    duplicates::find(graph.lockfile())
+        .into_iter()
+        .map(|duplicate| {
+            let (&target, older) = duplicate
+                .packages
+                .split_last()
+                .expect("a duplicate has at least two packages");
+            let outliers = older
+                .iter()
+                .map(|&package| Outlier {
+                    package,
+                    required_by: requirers(graph, manifests, package, target, older),
+                })
+                .collect();
+            Advice {
+                name: duplicate.name,
+                target,
+                outliers,
+            }
+        })
+        .collect()
+}
+
+fn requirers<'a>(
+    graph: &Graph<'a>,
+    manifests: &[Manifest],
+    outlier: &'a Package,
+    target: &'a Package,
+    older: &[&'a Package],
+) -> Vec<Requirer<'a>> {
+    let id = graph
+        .id(outlier)
+        .expect("outliers borrow from the lockfile");
+    graph
+        .dependents(id)
+        .iter()
+        .filter(|&&dependent| !graph.is_intentional(dependent, id))
+        .map(|&dependent| graph.package(dependent))
+        // An older copy that needs another older copy goes away with it.
+        .filter(|&dependent| !older.iter().any(|&o| std::ptr::eq(o, dependent)))
+        .map(|dependent| match find_manifest(manifests, dependent) {
+            Some(manifest) if dependent.source.is_none() => Requirer::Local {
+                package: dependent,
+                manifest: manifest.path.clone(),
+                changes: manifest
+                    .dependencies
+                    .iter()
+                    .filter(|d| requires(d, outlier, manifests))
+                    .map(|d| change(manifest, d, target, manifests))
+                    .collect(),
+            },
+            _ => Requirer::Upstream {
+                package: dependent,
+                used_by: local_dependents(graph, dependent),
+            },
+        })
+        .collect()
+}
+
+fn find_manifest<'m>(manifests: &'m [Manifest], package: &Package) -> Option<&'m Manifest> {
+    manifests
+        .iter()
+        .find(|m| m.name.as_deref() == Some(&package.name) && m.version == package.version)
+}
+
+// Whether `dependency` is the entry that resolved to `package`.
+fn requires(dependency: &Dependency, package: &Package, manifests: &[Manifest]) -> bool {
+    if dependency.package != package.name {
+        return false;
+    }
+    match &dependency.path {
+        Some(dir) => find_manifest(manifests, package).is_some_and(|m| m.dir() == dir),
+        None if package.source.is_none() => false,
+        None => dependency
+            .req
+            .as_deref()
+            .and_then(|req| req.parse::<VersionReq>().ok())
+            .is_some_and(|req| req.matches(&package.version)),
+    }
+}
+
+// Rewrites the entry to require the target's compatible range, pointing path
+// dependencies at the target's directory, or dropping the path if the target
+// isn't local.
+fn change(
+    manifest: &Manifest,
+    dependency: &Dependency,
+    target: &Package,
+    manifests: &[Manifest],
+) -> Change {
+    let req = toml::Value::String(semver::compatible_requirement(&target.version));
+    let after = match &dependency.value {
+        toml::Value::Table(table) => {
+            let mut table = table.clone();
+            table.insert("version".into(), req);
+            table.remove("path");
+            if target.source.is_none() {
+                if let Some(dir) = find_manifest(manifests, target).map(Manifest::dir) {
+                    let path = manifest::relative(manifest.dir(), dir);
+                    table.insert(
+                        "path".into(),
+                        toml::Value::String(path.to_string_lossy().into_owned()),
+                    );
+                }
+            }
+            toml::Value::Table(table)
+        }
+        _ => req,
+    };
+    Change {
+        section: dependency.section.clone(),
+        before: format!("{} = {}", dependency.key, render(&dependency.value)),
+        after: format!("{} = {}", dependency.key, render(&after)),
+    }
+}
+
+// The local packages that reach `package` through non-local packages only.
+fn local_dependents<'a>(graph: &Graph<'a>, package: &'a Package) -> Vec<&'a Package> {
+    let mut seen = BTreeSet::new();
+    let mut local = BTreeSet::new();
+    let mut stack = vec![graph
+        .id(package)
+        .expect("packages borrow from the lockfile")];
+    while let Some(id) = stack.pop() {
+        for &dependent in graph.dependents(id) {
+            if !seen.insert(dependent) {
+                continue;
+            }
+            if graph.package(dependent).source.is_none() {
+                local.insert(dependent);
+            } else {
+                stack.push(dependent);
+            }
+        }
+    }
+    local.into_iter().map(|id| graph.package(id)).collect()
+}
+
+// Cargo.toml style, with `package`, `version` and `path` leading inline
+// tables the way they're usually written.
+fn render(value: &toml::Value) -> String {
+    match value {
+        toml::Value::String(s) => format!("{s:?}"),
+        toml::Value::Integer(i) => i.to_string(),
+        toml::Value::Float(f) => f.to_string(),
+        toml::Value::Datetime(d) => d.to_string(),
+        toml::Value::Boolean(b) => b.to_string(),
+        toml::Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(render).collect();
+            format!("[{}]", items.join(", "))
+        }
+        toml::Value::Table(table) => {
+            let mut keys: Vec<&String> = table.keys().collect();
+            keys.sort_by_key(|k| {
+                let rank = ["package", "version", "path"].iter().position(|l| l == k);
+                (rank.unwrap_or(3), k.as_str())
+            });
+            let entries: Vec<String> = keys
+                .into_iter()
+                .map(|k| format!("{k} = {}", render(&table[k])))
+                .collect();
+            format!("{{ {} }}", entries.join(", "))
+        }
+    }
+}
@@ -0,0 +1,72 @@
+//! Runs Cargo on a workspace. Every command that builds, runs or resolves a
+//! scratch workspace goes through [`run`], so they all find Cargo the same
+//! way and fail with the same error.
+
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::process::{Command, Output};
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    /// Cargo ran and failed.
+    Failed {
+        subcommand: String,
+        stderr: String,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "failed to run cargo: {e}"),
+            Error::Failed { subcommand, stderr } => {
+                write!(f, "cargo {subcommand} failed:\n{}", stderr.trim_end())
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Failed { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+/// Runs `cargo <args>` on the workspace at `dir`, with `dir/target` as the
+/// target directory. Uses the `cargo` that is running us, if any.
+pub fn run(dir: &Path, args: &[&str]) -> Result<Output, Error> {
+    run_with_env(dir, args, &[])
+}
+
+/// Like [`run`], with extra environment variables. These can replace
+/// `CARGO_TARGET_DIR`.
+pub fn run_with_env(dir: &Path, args: &[&str], vars: &[(&str, &OsStr)]) -> Result<Output, Error> {
+    let cargo = env::var_os("CARGO").unwrap_or_else(|| OsString::from("cargo"));
+    let output = Command::new(cargo)
+        .args(args)
+        .arg("--manifest-path")
+        .arg(dir.join("Cargo.toml"))
+        .env("CARGO_TARGET_DIR", dir.join("target"))
+        .envs(vars.iter().copied())
+        .output()?;
+    if !output.status.success() {
+        return Err(Error::Failed {
+            subcommand: args.first().copied().unwrap_or_default().to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        });
+    }
+    Ok(output)
+}
@@ -0,0 +1,261 @@
+//! Measures what a duplicated dependency costs by building a copy of this
+//! workspace twice: once as it is, and once with `b` moved from log 0.3.9 to
+//! a SemVer compatible log 0.4. log 0.3.9 stays in both builds, since
+//! `bridge` and the binary itself require it too, so the difference is what
+//! `b` pays for speaking log 0.3.
+
+use std::collections::BTreeSet;
+use std::env;
+use std::ffi::OsString;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use object::read::archive::ArchiveFile;
+use serde_json::Value;
+
+use crate::cargo;
+use crate::depinfo;
+use crate::semver::Version;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Object(object::Error),
+    Cargo(cargo::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "{e}"),
+            Error::Object(e) => write!(f, "not a supported archive: {e}"),
+            Error::Cargo(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Object(e) => Some(e),
+            Error::Cargo(e) => Some(e),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<object::Error> for Error {
+    fn from(e: object::Error) -> Self {
+        Error::Object(e)
+    }
+}
+
+impl From<cargo::Error> for Error {
+    fn from(e: cargo::Error) -> Self {
+        Error::Cargo(e)
+    }
+}
+
+/// The crate whose `log` requirement differs between the two builds.
+pub const CRATE: &str = "b";
+/// The `log` requirement `b` has in each build.
+pub const SPLIT: &str = "0.3.9";
+pub const UNIFIED: &str = "0.4";
+
+// What moving `b` to log 0.4 takes, per file: its requirement, the log 0.3
+// names that log 0.4 renamed, and the binary's conversions of `b`'s levels,
+// which are log 0.4 types already.
+const EDITS: [(&str, &[(&str, &str)]); 3] = [
+    ("b/Cargo.toml", &[("log = \"0.3.9\"", "log = \"0.4\"")]),
+    (
+        "b/src/lib.rs",
+        &[
+            ("LogLevelFilter", "LevelFilter"),
+            ("LogLevel", "Level"),
+            ("max_log_level", "max_level"),
+        ],
+    ),
+    (
+        "src/main.rs",
+        &[
+            ("bridge::level_filter_to_04(filter)", "filter"),
+            ("bridge::level_to_04(level)", "level"),
+        ],
+    ),
+];
+
+/// Copies the workspace at `source` to `dir`, leaving out `target`
+/// directories and the git repository. An earlier copy in `dir` is replaced,
+/// apart from its `target` directory.
+pub fn copy_workspace(source: &Path, dir: &Path) -> io::Result<()> {
+    if dir.exists() {
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            if entry.file_name() == "target" {
+                continue;
+            }
+            if entry.file_type()?.is_dir() {
+                fs::remove_dir_all(entry.path())?;
+            } else {
+                fs::remove_file(entry.path())?;
+            }
+        }
+    }
+    copy_dir(source, dir)
+}
+
+/// Moves `b` to log 0.4 in the workspace copy at `dir`. Fails if one of the
+/// edits no longer matches the sources.
+pub fn unify(dir: &Path) -> io::Result<()> {
+    for (file, edits) in EDITS {
+        let path = dir.join(file);
+        let mut contents = fs::read_to_string(&path)?;
+        for (from, to) in edits {
+            if !contents.contains(from) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{} has no `{from}` to move to log 0.4", path.display()),
+                ));
+            }
+            contents = contents.replace(from, to);
+        }
+        fs::write(path, contents)?;
+    }
+    Ok(())
+}
+
+fn copy_dir(from: &Path, to: &Path) -> io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        if name == "target" || name == ".git" {
+            continue;
+        }
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &to.join(&name))?;
+        } else {
+            fs::copy(entry.path(), to.join(&name))?;
+        }
+    }
+    Ok(())
+}
+
+/// The result of one release build.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Build {
+    pub executable_bytes: u64,
+    /// Sorted by name, then version, then size.
+    pub crates: Vec<Compiled>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Compiled {
+    pub name: String,
+    pub version: Option<Version>,
+    pub rlib_bytes: u64,
+    /// The number of object files in the rlib.
+    pub codegen_units: usize,
+}
+
+impl Build {
+    pub fn rlib_bytes(&self) -> u64 {
+        self.crates.iter().map(|c| c.rlib_bytes).sum()
+    }
+
+    pub fn codegen_units(&self) -> usize {
+        self.crates.iter().map(|c| c.codegen_units).sum()
+    }
+
+    /// The smaller one, if the crate was compiled twice.
+    pub fn find(&self, name: &str, version: Option<&Version>) -> Option<&Compiled> {
+        self.crates
+            .iter()
+            .find(|c| c.name == name && c.version.as_ref() == version)
+    }
+}
+
+/// Runs `cargo build --release` in the workspace at `dir` and measures the
+/// result, including the executable `bin`.
+pub fn build(dir: &Path, bin: &str) -> Result<Build, Error> {
+    let output = cargo::run(
+        dir,
+        &["build", "--release", "--quiet", "--message-format=json"],
+    )?;
+    // `target` outlives earlier copies of the workspace, so only the rlibs
+    // Cargo reports for this build count, whether rebuilt or up to date.
+    let rlibs: BTreeSet<OsString> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .filter(|message| message["reason"] == "compiler-artifact")
+        .filter_map(|message| message["filenames"].as_array().cloned())
+        .flatten()
+        .filter_map(|filename| Some(Path::new(filename.as_str()?).file_name()?.to_owned()))
+        .collect();
+    measure(dir, &dir.join("target/release"), bin, &rlibs)
+}
+
+fn measure(
+    dir: &Path,
+    profile_dir: &Path,
+    bin: &str,
+    rlibs: &BTreeSet<OsString>,
+) -> Result<Build, Error> {
+    let executable_bytes =
+        fs::metadata(profile_dir.join(format!("{bin}{}", env::consts::EXE_SUFFIX)))?.len();
+    let deps = profile_dir.join("deps");
+    let mut crates = Vec::new();
+    for artifact in depinfo::artifacts(&deps, dir)? {
+        if !artifact.outputs.iter().any(|o| o == "rlib") {
+            continue;
+        }
+        let name = format!("lib{}-{}.rlib", artifact.name, artifact.hash);
+        if !rlibs.contains(OsString::from(&name).as_os_str()) {
+            continue;
+        }
+        let rlib: PathBuf = deps.join(name);
+        let codegen_units = codegen_units(&rlib)?;
+        crates.push(Compiled {
+            name: artifact.name,
+            version: artifact.version,
+            rlib_bytes: fs::metadata(&rlib)?.len(),
+            codegen_units,
+        });
+    }
+    // A build dependency that is also a normal one is compiled twice, with
+    // different settings. Sizes keep the two in the same order in every
+    // build.
+    crates.sort_by(|a, b| {
+        (&a.name, &a.version, a.rlib_bytes, a.codegen_units).cmp(&(
+            &b.name,
+            &b.version,
+            b.rlib_bytes,
+            b.codegen_units,
+        ))
+    });
+    Ok(Build {
+        executable_bytes,
+        crates,
+    })
+}
+
+/// The number of object files in the rlib at `path`, one per codegen unit.
+/// The other member is the crate's `lib.rmeta`.
+pub fn codegen_units(path: &Path) -> Result<usize, Error> {
+    let data = fs::read(path)?;
+    let mut count = 0;
+    for member in ArchiveFile::parse(&*data)?.members() {
+        if member?.name().ends_with(b".o") {
+            count += 1;
+        }
+    }
+    Ok(count)
+}
@@ -0,0 +1,216 @@
+//! Reads the Makefile-style `.d` dep-info files rustc writes next to every
+//! artifact in `target/<profile>/deps`, and works out which crate each metadata
+//! hash was built from.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::semver::Version;
+
+/// One `target: prerequisites...` line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    pub target: PathBuf,
+    pub prerequisites: Vec<PathBuf>,
+}
+
+/// Parses a dep-info file. Comments (rustc writes `# env-dep:` lines) and the
+/// empty rules it adds for every source file are skipped.
+pub fn parse(contents: &str) -> Vec<Rule> {
+    let contents = contents.replace("\\\n", " ");
+    let mut rules = Vec::new();
+    for line in contents.lines() {
+        if line.starts_with('#') {
+            continue;
+        }
+        let mut words = split_words(line).into_iter();
+        let Some(target) = words.next() else {
+            continue;
+        };
+        let Some(target) = target.strip_suffix(':') else {
+            continue;
+        };
+        let prerequisites: Vec<PathBuf> = words.map(PathBuf::from).collect();
+        if !prerequisites.is_empty() {
+            rules.push(Rule {
+                target: PathBuf::from(target),
+                prerequisites,
+            });
+        }
+    }
+    rules
+}
+
+// Splits on unescaped spaces, undoing `\ ` and `\\`.
+fn split_words(line: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(next @ (' ' | '\\' | '#')) => word.push(next),
+                Some(next) => {
+                    word.push('\\');
+                    word.push(next);
+                }
+                None => word.push('\\'),
+            },
+            ' ' | '\t' => {
+                if !word.is_empty() {
+                    words.push(std::mem::take(&mut word));
+                }
+            }
+            _ => word.push(c),
+        }
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+/// A crate compiled into `target/<profile>/deps`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Artifact {
+    /// The crate name, with `-` already turned into `_` by Cargo.
+    pub name: String,
+    /// The metadata hash Cargo appends to every file name.
+    pub hash: String,
+    /// `None` when neither the source directory's name nor its `Cargo.toml`
+    /// gives a plain version, e.g. with `version.workspace = true`.
+    pub version: Option<Version>,
+    /// The directory holding the crate's `Cargo.toml`, as written in the
+    /// dep-info file (relative paths are relative to the workspace root).
+    pub source_dir: PathBuf,
+    /// The extensions of the files built, e.g. `["rlib", "rmeta"]`. `cargo
+    /// check` only produces `rmeta`.
+    pub outputs: Vec<String>,
+}
+
+/// Reads every `<name>-<hash>.d` file in `deps_dir`. `base` is the directory
+/// relative source paths are resolved against when looking for `Cargo.toml`.
+pub fn artifacts(deps_dir: impl AsRef<Path>, base: impl AsRef<Path>) -> io::Result<Vec<Artifact>> {
+    let mut artifacts = Vec::new();
+    for entry in fs::read_dir(deps_dir)? {
+        let path = entry?.path();
+        if path.extension().is_none_or(|e| e != "d") {
+            continue;
+        }
+        let Some((name, hash)) = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .and_then(|s| s.rsplit_once('-'))
+        else {
+            continue;
+        };
+        // Every build script is called `build_script_build`.
+        if name.starts_with("build_script_") {
+            continue;
+        }
+        let rules = parse(&fs::read_to_string(&path)?);
+        if let Some(artifact) = artifact(name, hash, &rules, base.as_ref()) {
+            artifacts.push(artifact);
+        }
+    }
+    artifacts.sort_by(|a, b| a.name.cmp(&b.name).then(a.hash.cmp(&b.hash)));
+    Ok(artifacts)
+}
+
+fn artifact(name: &str, hash: &str, rules: &[Rule], base: &Path) -> Option<Artifact> {
+    let root = rules
+        .first()?
+        .prerequisites
+        .iter()
+        .find(|p| p.extension().is_some_and(|e| e == "rs"))?;
+    let source_dir = root
+        .ancestors()
+        .skip(1)
+        .find(|dir| base.join(dir).join("Cargo.toml").is_file())
+        .unwrap_or_else(|| root.parent().unwrap_or(root))
+        .to_path_buf();
+    let mut outputs: Vec<String> = rules
+        .iter()
+        .filter_map(|rule| Some(rule.target.extension()?.to_str()?.to_string()))
+        .filter(|e| e != "d")
+        .collect();
+    outputs.sort();
+    outputs.dedup();
+    Some(Artifact {
+        name: name.to_string(),
+        hash: hash.to_string(),
+        version: version_from_dir_name(&source_dir)
+            .or_else(|| version_from_manifest(&base.join(&source_dir))),
+        source_dir,
+        outputs,
+    })
+}
+
+// Registry and vendored crates live in `<package>-<version>` directories. The
+// version may itself contain `-`, so try every split from the left.
+fn version_from_dir_name(dir: &Path) -> Option<Version> {
+    let name = dir.file_name()?.to_str()?;
+    name.match_indices('-')
+        .find_map(|(i, _)| name[i + 1..].parse().ok())
+}
+
+fn version_from_manifest(dir: &Path) -> Option<Version> {
+    let manifest =
+        toml::from_str::<toml::Table>(&fs::read_to_string(dir.join("Cargo.toml")).ok()?).ok()?;
+    manifest
+        .get("package")?
+        .as_table()?
+        .get("version")?
+        .as_str()?
+        .parse()
+        .ok()
+}
+
+/// A crate name built from more than one source directory or version.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Duplicate<'a> {
+    pub name: &'a str,
+    /// Every source the name was built from, lowest version first.
+    pub sources: Vec<Source<'a>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Source<'a> {
+    pub version: Option<&'a Version>,
+    pub dir: &'a Path,
+    /// Usually more than one: `cargo check`, `cargo build` and `cargo test`
+    /// each get their own hash for the same source.
+    pub artifacts: Vec<&'a Artifact>,
+}
+
+/// Returns one entry per crate name compiled from more than one source, sorted
+/// by name.
+pub fn duplicates(artifacts: &[Artifact]) -> Vec<Duplicate<'_>> {
+    type BySource<'a> = BTreeMap<(Option<&'a Version>, &'a Path), Vec<&'a Artifact>>;
+    let mut by_name: BTreeMap<&str, BySource> = BTreeMap::new();
+    for artifact in artifacts {
+        by_name
+            .entry(&artifact.name)
+            .or_default()
+            .entry((artifact.version.as_ref(), &artifact.source_dir))
+            .or_default()
+            .push(artifact);
+    }
+    by_name
+        .into_iter()
+        .filter(|(_, sources)| sources.len() > 1)
+        .map(|(name, sources)| Duplicate {
+            name,
+            sources: sources
+                .into_iter()
+                .map(|((version, dir), artifacts)| Source {
+                    version,
+                    dir,
+                    artifacts,
+                })
+                .collect(),
+        })
+        .collect()
+}
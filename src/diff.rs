@@ -0,0 +1,148 @@
+//! Compares two lockfiles: which packages were added, removed or moved to
+//! another version, and which packages started or stopped resolving to more
+//! than one version.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::duplicates;
+use crate::lockfile::{Lockfile, Package};
+use crate::semver::{self, Version};
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Diff<'a> {
+    pub added: Vec<&'a Package>,
+    pub removed: Vec<&'a Package>,
+    /// `(old, new)` pairs of the same package name.
+    pub bumped: Vec<(&'a Package, &'a Package)>,
+    pub duplicates: Vec<DuplicateChange<'a>>,
+}
+
+/// A package whose set of resolved versions changed, where either side has
+/// more than one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicateChange<'a> {
+    pub name: &'a str,
+    /// Lowest version first.
+    pub old: Vec<&'a Package>,
+    pub new: Vec<&'a Package>,
+}
+
+impl DuplicateChange<'_> {
+    /// The package is duplicated now but wasn't before.
+    pub fn appeared(&self) -> bool {
+        self.old.len() <= 1
+    }
+
+    /// The package was duplicated before but isn't now.
+    pub fn resolved(&self) -> bool {
+        self.new.len() <= 1
+    }
+}
+
+impl Diff<'_> {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.bumped.is_empty()
+            && self.duplicates.is_empty()
+    }
+}
+
+// Packages are the same if their name, version and source all match.
+fn key(package: &Package) -> (&str, &Version, Option<&str>) {
+    (&package.name, &package.version, package.source.as_deref())
+}
+
+/// Everything sorted by package name, then version.
+pub fn diff<'a>(old: &'a Lockfile, new: &'a Lockfile) -> Diff<'a> {
+    let old_keys: BTreeSet<_> = old.packages.iter().map(key).collect();
+    let new_keys: BTreeSet<_> = new.packages.iter().map(key).collect();
+
+    // Per name: the packages only in the old lockfile and only in the new one.
+    let mut changed: BTreeMap<&str, (Vec<&Package>, Vec<&Package>)> = BTreeMap::new();
+    for package in &old.packages {
+        if !new_keys.contains(&key(package)) {
+            changed.entry(&package.name).or_default().0.push(package);
+        }
+    }
+    for package in &new.packages {
+        if !old_keys.contains(&key(package)) {
+            changed.entry(&package.name).or_default().1.push(package);
+        }
+    }
+
+    let mut diff = Diff::default();
+    for (gone, came) in changed.into_values() {
+        // One version on each side is that package moving, however far:
+        // log 0.3.9 → 0.4.22 is a bump.
+        if let ([old], [new]) = (gone.as_slice(), came.as_slice()) {
+            diff.bumped.push((old, new));
+            continue;
+        }
+        // Otherwise only versions in the same compatible range are one
+        // package moving: 0.4.21 → {0.3.9, 0.4.22} is 0.4.21 → 0.4.22 plus an
+        // added 0.3.9.
+        let mut ranges: BTreeMap<String, (Vec<&Package>, Vec<&Package>)> = BTreeMap::new();
+        for package in gone {
+            ranges
+                .entry(semver::compatible_requirement(&package.version))
+                .or_default()
+                .0
+                .push(package);
+        }
+        for package in came {
+            ranges
+                .entry(semver::compatible_requirement(&package.version))
+                .or_default()
+                .1
+                .push(package);
+        }
+        for (mut gone, mut came) in ranges.into_values() {
+            gone.sort_by(|a, b| a.version.cmp(&b.version));
+            came.sort_by(|a, b| a.version.cmp(&b.version));
+            // Pair them up lowest first, so two copies of 0.3 move in order.
+            let pairs = gone.len().min(came.len());
+            diff.bumped.extend(
+                gone[..pairs]
+                    .iter()
+                    .copied()
+                    .zip(came[..pairs].iter().copied()),
+            );
+            diff.removed.extend(&gone[pairs..]);
+            diff.added.extend(&came[pairs..]);
+        }
+    }
+    // The ranges are keyed by requirement text, where "0.10" sorts before
+    // "0.4".
+    diff.added.sort_by_key(|&p| key(p));
+    diff.removed.sort_by_key(|&p| key(p));
+    diff.bumped.sort_by_key(|&(old, _)| key(old));
+
+    let mut versions: BTreeMap<&str, (Vec<&Package>, Vec<&Package>)> = BTreeMap::new();
+    for duplicate in duplicates::find(old) {
+        versions.entry(duplicate.name).or_default().0 = duplicate.packages;
+    }
+    for duplicate in duplicates::find(new) {
+        versions.entry(duplicate.name).or_default().1 = duplicate.packages;
+    }
+    for (name, (mut old_packages, mut new_packages)) in versions {
+        // A package that stopped being duplicated still has one version left
+        // (or none, if it went away entirely).
+        if old_packages.is_empty() {
+            old_packages = old.packages.iter().filter(|p| p.name == name).collect();
+        }
+        if new_packages.is_empty() {
+            new_packages = new.packages.iter().filter(|p| p.name == name).collect();
+        }
+        let keys =
+            |packages: &[&'a Package]| -> Vec<_> { packages.iter().map(|&p| key(p)).collect() };
+        if keys(&old_packages) != keys(&new_packages) {
+            diff.duplicates.push(DuplicateChange {
+                name,
+                old: old_packages,
+                new: new_packages,
+            });
+        }
+    }
+    diff
+}
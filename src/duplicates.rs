@@ -0,0 +1,29 @@
+//! Finds packages that resolve to more than one version in a lockfile.
+
+use std::collections::BTreeMap;
+
+use crate::lockfile::{Lockfile, Package};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Duplicate<'a> {
+    pub name: &'a str,
+    /// Every resolved copy of the package, lowest version first.
+    pub packages: Vec<&'a Package>,
+}
+
+/// Returns one entry per package name with more than one resolved version,
+/// sorted by name.
+pub fn find(lockfile: &Lockfile) -> Vec<Duplicate<'_>> {
+    let mut by_name: BTreeMap<&str, Vec<&Package>> = BTreeMap::new();
+    for package in &lockfile.packages {
+        by_name.entry(&package.name).or_default().push(package);
+    }
+    by_name
+        .into_iter()
+        .filter(|(_, packages)| packages.len() > 1)
+        .map(|(name, mut packages)| {
+            packages.sort_by(|a, b| a.version.cmp(&b.version).then(a.source.cmp(&b.source)));
+            Duplicate { name, packages }
+        })
+        .collect()
+}
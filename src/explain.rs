@@ -0,0 +1,330 @@
+//! pip's `ResolutionImpossible` report for a [`Conflict`] found by the
+//! one-version-per-name strategy. The report lists the root requirements
+//! that can't be installed together and the requirements that clash. It
+//! then lists the changes to the index's packages that would get past the
+//! clash.
+
+use std::collections::BTreeSet;
+use std::fmt;
+
+use crate::resolve::{Conflict, Demand, Dependency, Index, Release};
+use crate::semver::{Comparator, Op, Version, VersionReq};
+
+const HELP: &str =
+    "https://pip.pypa.io/en/latest/topics/dependency-resolution/#dealing-with-dependency-conflicts";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Explanation {
+    /// The root's own requirements that lead to the clash.
+    pub requested: Vec<Dependency>,
+    /// The clashing requirements, the one that failed first. Only that one
+    /// if no release matches it at all.
+    pub causes: Vec<Demand>,
+    pub fixes: Vec<Fix>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Fix {
+    /// Relax one of the root's own requirements so that it accepts `to`.
+    Loosen { dependency: Dependency, to: Version },
+    /// Move a dependent to another of its releases, one whose requirement
+    /// fits with the others.
+    Switch {
+        name: String,
+        from: Version,
+        to: Version,
+    },
+    /// No release of the dependent accepts `version` of `package`, the newest
+    /// one the other requirements allow.
+    Update {
+        name: String,
+        from: Version,
+        package: String,
+        version: Version,
+    },
+}
+
+/// Explains `conflict`, which resolving `root` against `index` ran into.
+pub fn explain(index: &Index, root: &[Dependency], conflict: &Conflict) -> Explanation {
+    let mut causes = vec![(*conflict.wanted).clone()];
+    for chosen in &conflict.chosen {
+        for demand in &chosen.demanded_by {
+            if !causes.contains(demand) {
+                causes.push(demand.clone());
+            }
+        }
+    }
+
+    let mut requested: Vec<Dependency> = Vec::new();
+    for cause in &causes {
+        let reasons: Vec<&Dependency> = match &cause.by {
+            None => vec![&cause.dependency],
+            Some((name, _)) => root.iter().filter(|d| reaches(index, d, name)).collect(),
+        };
+        for reason in reasons {
+            if !requested.contains(reason) {
+                requested.push(reason.clone());
+            }
+        }
+    }
+    requested.sort_by_key(|d| root.iter().position(|r| r == d));
+
+    let fixes = if conflict.chosen.is_empty() {
+        Vec::new()
+    } else {
+        causes
+            .iter()
+            .enumerate()
+            .filter_map(|(i, cause)| {
+                let others: Vec<&Demand> = causes
+                    .iter()
+                    .enumerate()
+                    .filter(|&(j, _)| j != i)
+                    .map(|(_, other)| other)
+                    .collect();
+                fix(index, cause, &others)
+            })
+            .collect()
+    };
+
+    Explanation {
+        requested,
+        causes,
+        fixes,
+    }
+}
+
+// Whether any release of `dependency` leads to a package called `name`.
+fn reaches(index: &Index, dependency: &Dependency, name: &str) -> bool {
+    let mut seen = BTreeSet::from([dependency.name.as_str()]);
+    let mut stack = vec![dependency.name.as_str()];
+    while let Some(current) = stack.pop() {
+        if current == name {
+            return true;
+        }
+        for release in index.releases(current) {
+            for dep in &release.dependencies {
+                if seen.insert(&dep.name) {
+                    stack.push(&dep.name);
+                }
+            }
+        }
+    }
+    false
+}
+
+// What would let `cause` give way to the `others`.
+fn fix(index: &Index, cause: &Demand, others: &[&Demand]) -> Option<Fix> {
+    let package = &cause.dependency.name;
+    let fits: Vec<&Version> = index
+        .releases(package)
+        .iter()
+        .filter(|r| others.iter().all(|o| o.dependency.req.matches(&r.version)))
+        .filter(|r| installable(index, r, others))
+        .map(|r| &r.version)
+        .collect();
+    let Some((name, from)) = &cause.by else {
+        return fits.last().map(|&to| Fix::Loosen {
+            dependency: cause.dependency.clone(),
+            to: to.clone(),
+        });
+    };
+    let switch = index.releases(name).iter().rev().find(|release| {
+        release.version != *from
+            && installable(index, release, others)
+            && release
+                .dependencies
+                .iter()
+                .filter(|d| d.name == *package)
+                .all(|d| fits.iter().any(|v| d.req.matches(v)))
+    });
+    match (switch, fits.last()) {
+        (Some(release), _) => Some(Fix::Switch {
+            name: name.clone(),
+            from: from.clone(),
+            to: release.version.clone(),
+        }),
+        (None, Some(&version)) => Some(Fix::Update {
+            name: name.clone(),
+            from: from.clone(),
+            package: package.clone(),
+            version: version.clone(),
+        }),
+        (None, None) => None,
+    }
+}
+
+// Whether `release` can be installed next to the `others` when there is one
+// version per name. log 0.3.9 can't be: it needs log 0.4 as well.
+fn installable(index: &Index, release: &Release, others: &[&Demand]) -> bool {
+    release.dependencies.iter().all(|dependency| {
+        if dependency.name == release.name {
+            return dependency.req.matches(&release.version);
+        }
+        index.releases(&dependency.name).iter().any(|candidate| {
+            dependency.req.matches(&candidate.version)
+                && others
+                    .iter()
+                    .filter(|o| o.dependency.name == dependency.name)
+                    .all(|o| o.dependency.req.matches(&candidate.version))
+        })
+    })
+}
+
+/// `req` as a PEP 440 version specifier, e.g. `>=0.3.9,<0.4` for `0.3.9`.
+/// Empty if it accepts any version, and `None` if it uses an operator PEP 440
+/// has no equivalent for.
+pub fn specifier(req: &VersionReq) -> Option<String> {
+    let clauses: Vec<Vec<String>> = req.comparators.iter().map(clauses).collect::<Option<_>>()?;
+    Some(clauses.concat().join(","))
+}
+
+fn clauses(comparator: &Comparator) -> Option<Vec<String>> {
+    let parts: Vec<u64> = [Some(comparator.major), comparator.minor, comparator.patch]
+        .into_iter()
+        .map_while(|part| part)
+        .collect();
+    let mut lower = join(&parts);
+    if !comparator.pre.is_empty() {
+        lower = format!("{lower}-{}", comparator.pre);
+    }
+    // Everything up to the first version past the leading `len` parts, e.g.
+    // `<0.4` for 0.3.9 and a length of 2.
+    let upper = |len: usize| {
+        let mut bumped = parts[..len].to_vec();
+        bumped[len - 1] += 1;
+        join(&bumped)
+    };
+    let clauses = match comparator.op {
+        Op::Wildcard if parts.is_empty() => Vec::new(),
+        Op::Wildcard => vec![format!("=={}.*", join(&parts))],
+        Op::Exact if parts.len() == 3 => vec![format!("=={lower}")],
+        Op::Exact => vec![format!("=={lower}.*")],
+        Op::Tilde => vec![
+            format!(">={lower}"),
+            format!("<{}", upper(parts.len().min(2))),
+        ],
+        Op::Caret => {
+            let significant = parts
+                .iter()
+                .position(|&part| part != 0)
+                .map_or(parts.len(), |i| i + 1);
+            vec![
+                format!(">={lower}"),
+                format!("<{}", upper(significant.min(parts.len()))),
+            ]
+        }
+        Op::Greater if parts.len() < 3 => vec![format!(">={}", upper(parts.len()))],
+        Op::Greater => vec![format!(">{lower}")],
+        Op::GreaterEq => vec![format!(">={lower}")],
+        Op::Less => vec![format!("<{lower}")],
+        Op::LessEq if parts.len() < 3 => vec![format!("<{}", upper(parts.len()))],
+        Op::LessEq => vec![format!("<={lower}")],
+        _ => return None,
+    };
+    Some(clauses)
+}
+
+fn join(parts: &[u64]) -> String {
+    let parts: Vec<String> = parts.iter().map(u64::to_string).collect();
+    parts.join(".")
+}
+
+// `log>=0.3.9,<0.4`, the way pip writes requirements, or `log (0.3.9)` with
+// Cargo's syntax if pip has none for it.
+fn requirement(dependency: &Dependency) -> String {
+    match specifier(&dependency.req) {
+        Some(specifier) => format!("{}{specifier}", dependency.name),
+        None => format!("{} ({})", dependency.name, dependency.req),
+    }
+}
+
+impl fmt::Display for Fix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Fix::Loosen { dependency, to } => write!(
+                f,
+                "loosen your requirement {} so that it accepts {} {to}",
+                requirement(dependency),
+                dependency.name
+            ),
+            Fix::Switch { name, from, to } => {
+                write!(f, "use {name} {to} instead of {name} {from}")
+            }
+            Fix::Update {
+                name,
+                from,
+                package,
+                version,
+            } => write!(
+                f,
+                "update {name} {from} to accept {package} {version}, or stop depending on {name}"
+            ),
+        }
+    }
+}
+
+/// pip's own wording, plus the concrete fixes in front of its generic ones.
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cause = |demand: &Demand| match &demand.by {
+            Some((name, version)) => format!(
+                "{name} {version} depends on {}",
+                requirement(&demand.dependency)
+            ),
+            None => format!("The user requested {}", requirement(&demand.dependency)),
+        };
+
+        if let [missing] = self.causes.as_slice() {
+            let from = match &missing.by {
+                Some((name, version)) => format!(" (from {name}=={version})"),
+                None => String::new(),
+            };
+            writeln!(
+                f,
+                "ERROR: Could not find a version that satisfies the requirement {}{from}",
+                requirement(&missing.dependency)
+            )?;
+            return writeln!(
+                f,
+                "ERROR: No matching distribution found for {}",
+                missing.dependency.name
+            );
+        }
+
+        let requested: Vec<String> = self.requested.iter().map(requirement).collect();
+        writeln!(
+            f,
+            "ERROR: Cannot install {} because these package versions have conflicting \
+             dependencies.",
+            and(&requested)
+        )?;
+        writeln!(f)?;
+        writeln!(f, "The conflict is caused by:")?;
+        for demand in &self.causes {
+            writeln!(f, "    {}", cause(demand))?;
+        }
+        writeln!(f)?;
+        writeln!(f, "To fix this you could try to:")?;
+        let generic = [
+            "loosen the range of package versions you've specified".to_string(),
+            "remove package versions to allow pip to attempt to solve the dependency conflict"
+                .to_string(),
+        ];
+        let fixes = self.fixes.iter().map(Fix::to_string).chain(generic);
+        for (i, fix) in fixes.enumerate() {
+            writeln!(f, "{}. {fix}", i + 1)?;
+        }
+        writeln!(f)?;
+        writeln!(f, "ERROR: ResolutionImpossible: for help visit {HELP}")
+    }
+}
+
+// `a`, `a and b`, `a, b and c`.
+fn and(items: &[String]) -> String {
+    match items {
+        [] => String::new(),
+        [only] => only.clone(),
+        [rest @ .., last] => format!("{} and {last}", rest.join(", ")),
+    }
+}
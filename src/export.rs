@@ -0,0 +1,146 @@
+//! Renders a lockfile's resolved dependency graph as Graphviz DOT or as a
+//! Mermaid flowchart, with duplicated packages and the edges that pull them in
+//! highlighted.
+
+use std::fmt::Write;
+
+use crate::duplicates;
+use crate::graph::Graph;
+
+/// The packages and edges to draw.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct View {
+    /// Package indices, in lockfile order.
+    pub nodes: Vec<usize>,
+    pub edges: Vec<(usize, usize)>,
+    /// The nodes whose package name resolves to more than one version.
+    pub duplicated: Vec<usize>,
+}
+
+impl View {
+    /// Every package and dependency in the lockfile.
+    pub fn full(graph: &Graph<'_>) -> Self {
+        Self::filtered(graph, |_| true)
+    }
+
+    /// Only the packages that are duplicated or depend on one, directly or
+    /// not: the part of the graph that explains the duplicates.
+    pub fn duplicates_only(graph: &Graph<'_>) -> Self {
+        let duplicated = duplicated(graph);
+        let mut keep = vec![false; graph.len()];
+        let mut stack = duplicated.clone();
+        while let Some(id) = stack.pop() {
+            if !keep[id] {
+                keep[id] = true;
+                stack.extend_from_slice(graph.dependents(id));
+            }
+        }
+        Self::filtered(graph, |id| keep[id])
+    }
+
+    fn filtered(graph: &Graph<'_>, keep: impl Fn(usize) -> bool) -> Self {
+        let nodes: Vec<usize> = (0..graph.len()).filter(|&id| keep(id)).collect();
+        let edges = nodes
+            .iter()
+            .flat_map(|&from| graph.dependencies(from).iter().map(move |&to| (from, to)))
+            .filter(|&(_, to)| keep(to))
+            .collect();
+        let duplicated = duplicated(graph)
+            .into_iter()
+            .filter(|&id| keep(id))
+            .collect();
+        View {
+            nodes,
+            edges,
+            duplicated,
+        }
+    }
+
+    fn is_duplicated(&self, id: usize) -> bool {
+        self.duplicated.contains(&id)
+    }
+}
+
+fn duplicated(graph: &Graph<'_>) -> Vec<usize> {
+    let mut ids: Vec<usize> = duplicates::find(graph.lockfile())
+        .iter()
+        .flat_map(|d| &d.packages)
+        .map(|&p| {
+            graph
+                .id(p)
+                .expect("duplicates borrow from the same lockfile")
+        })
+        .collect();
+    ids.sort();
+    ids
+}
+
+// Only duplicated packages need their version spelled out, as in `why`.
+fn label(graph: &Graph<'_>, view: &View, id: usize) -> String {
+    let package = graph.package(id);
+    if view.is_duplicated(id) {
+        package.to_string()
+    } else {
+        package.name.clone()
+    }
+}
+
+/// Graphviz DOT. Duplicated packages are filled red and the edges into them
+/// drawn in red.
+pub fn dot(graph: &Graph<'_>, view: &View) -> String {
+    let mut out = String::from("digraph dependencies {\n    node [shape=box];\n");
+    for &id in &view.nodes {
+        let label = label(graph, view, id);
+        if view.is_duplicated(id) {
+            writeln!(
+                out,
+                "    n{id} [label=\"{label}\", color=\"#cc0000\", style=filled, fillcolor=\"#ffdddd\"];"
+            )
+        } else {
+            writeln!(out, "    n{id} [label=\"{label}\"];")
+        }
+        .expect("writing to a String cannot fail");
+    }
+    for &(from, to) in &view.edges {
+        if view.is_duplicated(to) {
+            writeln!(out, "    n{from} -> n{to} [color=\"#cc0000\"];")
+        } else {
+            writeln!(out, "    n{from} -> n{to};")
+        }
+        .expect("writing to a String cannot fail");
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// A Mermaid flowchart, ready to paste into a ```` ```mermaid ```` block.
+/// Highlighting uses the same colors as [`dot`].
+pub fn mermaid(graph: &Graph<'_>, view: &View) -> String {
+    let mut out = String::from("graph TD\n");
+    for &id in &view.nodes {
+        writeln!(out, "    n{id}[\"{}\"]", label(graph, view, id))
+            .expect("writing to a String cannot fail");
+    }
+    let mut highlighted = Vec::new();
+    for (index, &(from, to)) in view.edges.iter().enumerate() {
+        writeln!(out, "    n{from} --> n{to}").expect("writing to a String cannot fail");
+        if view.is_duplicated(to) {
+            highlighted.push(index.to_string());
+        }
+    }
+    if !view.duplicated.is_empty() {
+        let nodes: Vec<String> = view.duplicated.iter().map(|id| format!("n{id}")).collect();
+        out.push_str("    classDef duplicate fill:#ffdddd,stroke:#cc0000\n");
+        writeln!(out, "    class {} duplicate", nodes.join(","))
+            .expect("writing to a String cannot fail");
+    }
+    if !highlighted.is_empty() {
+        writeln!(
+            out,
+            "    linkStyle {} stroke:#cc0000",
+            highlighted.join(",")
+        )
+        .expect("writing to a String cannot fail");
+    }
+    out
+}
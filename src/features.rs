@@ -0,0 +1,189 @@
+//! The other side of duplication: when `a` and `b` use the same version of
+//! a crate, Cargo builds it once with the union of the features they ask
+//! for. This writes a throwaway workspace that shows which features each
+//! user of a shared crate ends up with, under either feature resolver.
+//!
+//! `a` enables `x` and `b` enables `y` of the `shared` crate. `b` also asks
+//! for `build` from its build script, and for `never` in a target table
+//! whose `cfg` never matches. Resolver "1" unifies all four everywhere.
+//! Resolver "2" still unifies `x` and `y`, but it keeps build dependencies
+//! apart and drops features of targets that aren't being built.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::cargo;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Cargo(cargo::Error),
+    Invalid(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "{e}"),
+            Error::Cargo(e) => write!(f, "{e}"),
+            Error::Invalid(message) => write!(f, "unexpected output: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Cargo(e) => Some(e),
+            Error::Invalid(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<cargo::Error> for Error {
+    fn from(e: cargo::Error) -> Self {
+        Error::Cargo(e)
+    }
+}
+
+/// The feature resolvers Cargo offers.
+pub const RESOLVERS: [&str; 2] = ["1", "2"];
+
+/// Every feature of the shared crate, none of them on by default.
+pub const FEATURES: [&str; 4] = ["build", "never", "x", "y"];
+
+/// The features one user of the shared crate saw it compiled with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Seen {
+    /// `a`, `b`, or `b (build script)`.
+    pub by: String,
+    pub features: Vec<String>,
+}
+
+/// Writes the workspace to `dir`, using `resolver`.
+pub fn write(dir: &Path, resolver: &str) -> io::Result<()> {
+    let mut features = String::from("\n[features]\n");
+    let mut checks = String::new();
+    for feature in FEATURES {
+        features.push_str(&format!("{feature} = []\n"));
+        checks.push_str(&format!(
+            "    if cfg!(feature = \"{feature}\") {{\n        \
+             enabled.push(\"{feature}\");\n    }}\n"
+        ));
+    }
+    let files = [
+        ("shared/Cargo.toml", manifest("shared", &features)),
+        (
+            "shared/src/lib.rs",
+            format!(
+                "pub fn features() -> String {{\n    \
+                 let mut enabled: Vec<&str> = Vec::new();\n{checks}    \
+                 enabled.join(\",\")\n}}\n"
+            ),
+        ),
+        (
+            "a/Cargo.toml",
+            manifest(
+                "a",
+                "\n[dependencies]\n\
+                 shared = { path = \"../shared\", features = [\"x\"] }\n",
+            ),
+        ),
+        (
+            "a/src/lib.rs",
+            "pub fn shared_features() -> String {\n    shared::features()\n}\n".into(),
+        ),
+        (
+            "b/Cargo.toml",
+            manifest(
+                "b",
+                "\n[dependencies]\n\
+                 shared = { path = \"../shared\", features = [\"y\"] }\n\
+                 \n[build-dependencies]\n\
+                 shared = { path = \"../shared\", features = [\"build\"] }\n\
+                 \n[target.'cfg(any())'.dependencies]\n\
+                 shared = { path = \"../shared\", features = [\"never\"] }\n",
+            ),
+        ),
+        (
+            "b/build.rs",
+            "fn main() {\n    \
+             println!(\"cargo:rustc-env=SHARED_FEATURES_AT_BUILD={}\", shared::features());\n\
+             }\n"
+            .into(),
+        ),
+        (
+            "b/src/lib.rs",
+            "pub fn shared_features() -> String {\n    shared::features()\n}\n\n\
+             pub fn build_script_features() -> &'static str {\n    \
+             env!(\"SHARED_FEATURES_AT_BUILD\")\n}\n"
+                .into(),
+        ),
+        (
+            "Cargo.toml",
+            format!(
+                "{}\n[workspace]\nresolver = \"{resolver}\"\n",
+                manifest(
+                    "unification",
+                    "\n[dependencies]\n\
+                     a = { path = \"a\" }\n\
+                     b = { path = \"b\" }\n"
+                )
+            ),
+        ),
+        (
+            "src/main.rs",
+            "fn main() {\n    \
+             println!(\"a {}\", a::shared_features());\n    \
+             println!(\"b {}\", b::shared_features());\n    \
+             println!(\"b (build script) {}\", b::build_script_features());\n\
+             }\n"
+            .into(),
+        ),
+    ];
+    for (path, contents) in files {
+        let path = dir.join(path);
+        fs::create_dir_all(path.parent().expect("files are in a directory"))?;
+        fs::write(path, contents)?;
+    }
+    Ok(())
+}
+
+fn manifest(name: &str, rest: &str) -> String {
+    format!(
+        "[package]\n\
+         name = \"{name}\"\n\
+         version = \"0.1.0\"\n\
+         edition = \"2021\"\n{rest}"
+    )
+}
+
+/// Builds and runs the workspace written to `dir`.
+pub fn build(dir: &Path) -> Result<Vec<Seen>, Error> {
+    let output = cargo::run(dir, &["run", "--quiet", "--offline"])?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| {
+            let (by, features) = line
+                .rsplit_once(' ')
+                .ok_or_else(|| Error::Invalid(line.to_string()))?;
+            Ok(Seen {
+                by: by.to_string(),
+                features: features
+                    .split(',')
+                    .filter(|f| !f.is_empty())
+                    .map(String::from)
+                    .collect(),
+            })
+        })
+        .collect()
+}
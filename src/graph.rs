@@ -0,0 +1,149 @@
+//! The resolved dependency graph described by a lockfile.
+//!
+//! `Cargo.lock` only disambiguates a dependency when it has to: an entry is
+//! `"log"` when a single `log` is locked, `"log 0.4.22"` when several versions
+//! are, and `"log 0.4.22 (registry+...)"` when the same version comes from
+//! several sources. [`Graph::new`] resolves all three forms to package indices.
+
+use crate::lockfile::{Error, Lockfile, Package};
+
+#[derive(Debug, Clone)]
+pub struct Graph<'a> {
+    lockfile: &'a Lockfile,
+    dependencies: Vec<Vec<usize>>,
+    dependents: Vec<Vec<usize>>,
+    intentional: Vec<(usize, usize)>,
+}
+
+impl<'a> Graph<'a> {
+    pub fn new(lockfile: &'a Lockfile) -> Result<Self, Error> {
+        let mut dependencies = Vec::with_capacity(lockfile.packages.len());
+        let mut dependents = vec![Vec::new(); lockfile.packages.len()];
+        for (id, package) in lockfile.packages.iter().enumerate() {
+            let mut resolved = Vec::with_capacity(package.dependencies.len());
+            for dependency in &package.dependencies {
+                let dep = resolve(lockfile, dependency).ok_or_else(|| {
+                    Error::Invalid(format!(
+                        "dependency `{dependency}` of `{package}` does not match exactly one package"
+                    ))
+                })?;
+                resolved.push(dep);
+                dependents[dep].push(id);
+            }
+            dependencies.push(resolved);
+        }
+        Ok(Graph {
+            lockfile,
+            dependencies,
+            dependents,
+            intentional: Vec::new(),
+        })
+    }
+
+    pub fn lockfile(&self) -> &'a Lockfile {
+        self.lockfile
+    }
+
+    pub fn len(&self) -> usize {
+        self.lockfile.packages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lockfile.packages.is_empty()
+    }
+
+    pub fn package(&self, id: usize) -> &'a Package {
+        &self.lockfile.packages[id]
+    }
+
+    pub fn dependencies(&self, id: usize) -> &[usize] {
+        &self.dependencies[id]
+    }
+
+    pub fn dependents(&self, id: usize) -> &[usize] {
+        &self.dependents[id]
+    }
+
+    /// Marks `edges`, as `(dependent, dependency)` indices, as asked for on
+    /// purpose, e.g. the ones [`manifest::dual_edges`] finds.
+    ///
+    /// [`manifest::dual_edges`]: crate::manifest::dual_edges
+    pub fn set_intentional(&mut self, edges: Vec<(usize, usize)>) {
+        self.intentional = edges;
+    }
+
+    /// Whether `dependent` asked for this copy of `dependency` next to
+    /// another one on purpose. Reports that look for copies to unify skip
+    /// such edges.
+    pub fn is_intentional(&self, dependent: usize, dependency: usize) -> bool {
+        self.intentional.contains(&(dependent, dependency))
+    }
+
+    /// Packages nothing else depends on, i.e. the workspace members that were
+    /// built directly.
+    pub fn roots(&self) -> Vec<usize> {
+        (0..self.len())
+            .filter(|&id| self.dependents[id].is_empty())
+            .collect()
+    }
+
+    /// The index of a package borrowed from this graph's lockfile.
+    pub fn id(&self, package: &Package) -> Option<usize> {
+        self.lockfile
+            .packages
+            .iter()
+            .position(|p| std::ptr::eq(p, package))
+    }
+
+    /// Looks a package up by name, and by version if more than one is locked.
+    pub fn find(&self, name: &str, version: Option<&str>) -> Option<usize> {
+        let mut matches =
+            self.lockfile.packages.iter().enumerate().filter(|(_, p)| {
+                p.name == name && version.is_none_or(|v| p.version.to_string() == v)
+            });
+        let (id, _) = matches.next()?;
+        matches.next().is_none().then_some(id)
+    }
+
+    /// Every dependency path from `from` to `to` that visits no package
+    /// twice, each listed as the package indices along the way.
+    pub fn paths(&self, from: usize, to: usize) -> Vec<Vec<usize>> {
+        let mut paths = Vec::new();
+        let mut stack = vec![from];
+        self.walk(to, &mut stack, &mut paths);
+        paths
+    }
+
+    fn walk(&self, to: usize, stack: &mut Vec<usize>, paths: &mut Vec<Vec<usize>>) {
+        let current = *stack.last().expect("walk starts from a package");
+        if current == to {
+            paths.push(stack.clone());
+            return;
+        }
+        for &dep in &self.dependencies[current] {
+            if !stack.contains(&dep) {
+                stack.push(dep);
+                self.walk(to, stack, paths);
+                stack.pop();
+            }
+        }
+    }
+}
+
+fn resolve(lockfile: &Lockfile, dependency: &str) -> Option<usize> {
+    let (spec, source) = match dependency.split_once(" (") {
+        Some((spec, source)) => (spec, Some(source.strip_suffix(')')?)),
+        None => (dependency, None),
+    };
+    let (name, version) = match spec.split_once(' ') {
+        Some((name, version)) => (name, Some(version)),
+        None => (spec, None),
+    };
+    let mut matches = lockfile.packages.iter().enumerate().filter(|(_, p)| {
+        p.name == name
+            && version.is_none_or(|v| p.version.to_string() == v)
+            && source.is_none_or(|s| p.source.as_deref() == Some(s))
+    });
+    let (id, _) = matches.next()?;
+    matches.next().is_none().then_some(id)
+}
@@ -0,0 +1,22 @@
+pub mod advise;
+pub mod cargo;
+pub mod cost;
+pub mod depinfo;
+pub mod diff;
+pub mod duplicates;
+pub mod explain;
+pub mod export;
+pub mod features;
+pub mod graph;
+pub mod lockfile;
+pub mod manifest;
+pub mod npm;
+pub mod pip;
+pub mod policy;
+pub mod registry;
+pub mod resolve;
+pub mod sbom;
+pub mod scenario;
+pub mod semver;
+pub mod symbols;
+pub mod timings;
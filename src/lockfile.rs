@@ -0,0 +1,117 @@
+//! A typed view of `Cargo.lock`.
+
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+use crate::semver::Version;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Toml(toml::de::Error),
+    Invalid(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "failed to read lockfile: {e}"),
+            Error::Toml(e) => write!(f, "failed to parse lockfile: {e}"),
+            Error::Invalid(message) => write!(f, "invalid lockfile: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Toml(e) => Some(e),
+            Error::Invalid(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<toml::de::Error> for Error {
+    fn from(e: toml::de::Error) -> Self {
+        Error::Toml(e)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lockfile {
+    pub version: Option<i64>,
+    pub packages: Vec<Package>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Package {
+    pub name: String,
+    pub version: Version,
+    /// `None` for path dependencies and workspace members.
+    pub source: Option<String>,
+    pub checksum: Option<String>,
+    pub dependencies: Vec<String>,
+}
+
+impl fmt::Display for Package {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.name, self.version)
+    }
+}
+
+impl Lockfile {
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn parse(input: &str) -> Result<Self, Error> {
+        let document = toml::from_str::<toml::Table>(input)?;
+        let version = document.get("version").and_then(toml::Value::as_integer);
+        let packages = match document.get("package") {
+            None => Vec::new(),
+            Some(value) => value
+                .as_array()
+                .ok_or_else(|| Error::Invalid("`package` is not an array of tables".into()))?
+                .iter()
+                .map(package)
+                .collect::<Result<_, _>>()?,
+        };
+        Ok(Lockfile { version, packages })
+    }
+}
+
+fn package(value: &toml::Value) -> Result<Package, Error> {
+    let table = value
+        .as_table()
+        .ok_or_else(|| Error::Invalid("`package` entry is not a table".into()))?;
+    let string = |key: &str| table.get(key).and_then(toml::Value::as_str);
+    let name = string("name").ok_or_else(|| Error::Invalid("package without a name".into()))?;
+    let version = string("version")
+        .ok_or_else(|| Error::Invalid(format!("package `{name}` has no version")))?
+        .parse()
+        .map_err(|e| Error::Invalid(format!("package `{name}`: {e}")))?;
+    let dependencies = match table.get("dependencies") {
+        None => Vec::new(),
+        Some(deps) => deps
+            .as_array()
+            .and_then(|deps| deps.iter().map(|d| d.as_str().map(String::from)).collect())
+            .ok_or_else(|| {
+                Error::Invalid(format!("package `{name}` has malformed dependencies"))
+            })?,
+    };
+    Ok(Package {
+        name: name.to_string(),
+        version,
+        source: string("source").map(String::from),
+        checksum: string("checksum").map(String::from),
+        dependencies,
+    })
+}
@@ -1,11 +1,1191 @@
+// log 0.3's macros call each other by bare name, so they only work when
+// imported this way. See `macros()`.
+#[macro_use]
+extern crate log03;
+
+use std::collections::BTreeMap;
+use std::env;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitCode};
+
 use a::log as log_a;
 use b::log as log_b;
+use c::log as log_c;
+use rust_incompatible_transitive_version_example::advise::{self, Requirer};
+use rust_incompatible_transitive_version_example::cargo;
+use rust_incompatible_transitive_version_example::cost;
+use rust_incompatible_transitive_version_example::depinfo;
+use rust_incompatible_transitive_version_example::diff;
+use rust_incompatible_transitive_version_example::duplicates;
+use rust_incompatible_transitive_version_example::explain;
+use rust_incompatible_transitive_version_example::export::{self, View};
+use rust_incompatible_transitive_version_example::features;
+use rust_incompatible_transitive_version_example::graph::Graph;
+use rust_incompatible_transitive_version_example::lockfile::{self, Lockfile};
+use rust_incompatible_transitive_version_example::manifest;
+use rust_incompatible_transitive_version_example::npm;
+use rust_incompatible_transitive_version_example::pip;
+use rust_incompatible_transitive_version_example::policy::{self, Policy, Verdict};
+use rust_incompatible_transitive_version_example::registry;
+use rust_incompatible_transitive_version_example::resolve::{
+    self, Dependency, Index, Resolution, Strategy,
+};
+use rust_incompatible_transitive_version_example::sbom::{self, Sbom};
+use rust_incompatible_transitive_version_example::scenario::{self, Scenario};
+use rust_incompatible_transitive_version_example::semver::Version;
+use rust_incompatible_transitive_version_example::symbols;
+use rust_incompatible_transitive_version_example::timings;
 use simple_logger::SimpleLogger;
 
-fn main() {
+const USAGE: &str = "\
+usage: rust-incompatible-transitive-version-example [COMMAND]
+
+With no command, logs once through `a` (log 0.4) and once through `b` (log 0.3).
+
+commands:
+  duplicates [--deny] [LOCKFILE]   list packages resolved to more than one version
+  why [--root NAME] [LOCKFILE]     show every dependency path to each duplicated version
+  diff [OLD [NEW]]                 compare two lockfiles, each a path or a git `REV:PATH`
+                                   (default: HEAD:Cargo.lock against Cargo.lock)
+  advise [MANIFEST]                suggest the manifest edits that would unify each duplicate
+                                   (default: Cargo.toml, next to its Cargo.lock)
+  check [--policy FILE] [LOCKFILE] fail if duplicates break the policy (default: duplicates.toml)
+  graph [--mermaid] [--duplicates] [LOCKFILE]
+                                   print the dependency graph as Graphviz DOT (or Mermaid),
+                                   optionally only the part that leads to duplicates
+  sbom [--spdx] [--root NAME] [MANIFEST]
+                                   print a CycloneDX (or SPDX) SBOM listing every resolved version
+  resolve [cargo|pip|npm]          resolve the `a`/`b`/`log` graph the way each package manager
+                                   would (default: all three)
+  pip-check [MANIFEST]             resolve the workspace one version per name, like pip, and
+                                   explain the conflict if that fails (default: Cargo.toml)
+  ecosystems [--cargo LOCKFILE] [--npm PACKAGE_LOCK] [--pip REQUIREMENTS INDEX]
+                                   report whether Cargo, npm and pip accept their graphs
+                                   (default: Cargo.lock and the fixtures in fixtures/npm, fixtures/pip)
+  macros                           log through both log versions' macros straight from this crate
+  features [DIR]                   build `a` and `b` enabling different features of one shared crate
+                                   under each feature resolver (default: target/features)
+  links                            try to resolve `fixtures/links`, where `a` and `b` need two
+                                   versions of a `-sys` crate with the same `links` key
+  registry [publish]               pack the fixture log releases into target/local-registry and
+                                   resolve `a`/`b`-style crates against it, or just list them
+  scenario [--depth N] [--dir DIR] VERSION...
+                                   build a workspace with one chain of N crates per VERSION, each
+                                   ending in that version of a shared crate, and report the copies
+                                   (default: depth 1 in target/scenario)
+  semver-trick                     also log through `c` (log 0.3.8, no forwarding to 0.4)
+  bridge                           convert `b`'s log 0.3 max level and level, and log through `a`
+                                   with them
+  globals                          register plugins through `a` and `b` and list what each sees
+  symbols [--crate NAME] [BINARY]  attribute the code in an executable to crates and their versions
+                                   (default: this one, rebuilt with v0 symbols in target/symbols)
+  artifacts [DEPS_DIR]             list crates compiled more than once (default: target/debug/deps)
+  size-cost [SCRATCH_DIR]          build this workspace as it is and with `b` moved to log 0.4, and
+                                   compare sizes (default: target/size-cost)
+  compile-cost [TIMINGS_HTML]      attribute compile time to each duplicated crate, from a
+                                   `cargo build --timings` report (default: time a fresh build
+                                   of this workspace in target/compile-cost)";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        None => demo(),
+        Some("duplicates") => duplicates(&args[1..]),
+        Some("why") => why(&args[1..]),
+        Some("diff") => diff(&args[1..]),
+        Some("advise") => advise(&args[1..]),
+        Some("check") => check(&args[1..]),
+        Some("graph") => graph(&args[1..]),
+        Some("sbom") => sbom(&args[1..]),
+        Some("resolve") => resolve(&args[1..]),
+        Some("pip-check") => pip_check(&args[1..]),
+        Some("ecosystems") => ecosystems(&args[1..]),
+        Some("macros") => macros(),
+        Some("features") => features(&args[1..]),
+        Some("links") => links(),
+        Some("registry") => registry(&args[1..]),
+        Some("scenario") => scenario(&args[1..]),
+        Some("semver-trick") => semver_trick(),
+        Some("bridge") => bridge(),
+        Some("globals") => globals(),
+        Some("symbols") => symbols(&args[1..]),
+        Some("artifacts") => artifacts(&args[1..]),
+        Some("size-cost") => size_cost(&args[1..]),
+        Some("compile-cost") => compile_cost(&args[1..]),
+        Some("-h" | "--help" | "help") => {
+            println!("{USAGE}");
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(other) => {
+            eprintln!("unknown command `{other}`\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    result.unwrap_or_else(|e| {
+        eprintln!("error: {e}");
+        ExitCode::FAILURE
+    })
+}
+
+fn demo() -> Result<ExitCode, Box<dyn Error>> {
+    SimpleLogger::new()
+        .init()
+        .expect("Failed to initialize logger");
+    let a = log_a();
+    let b = log_b();
+
+    // Each row is reported by the crate's own copy of `log`.
+    println!();
+    print_provenance_header();
+    print_provenance(
+        a.crate_name,
+        a.log_version,
+        &a.max_level,
+        a.logger_installed,
+    );
+    print_provenance(
+        b.crate_name,
+        b.log_version,
+        &b.max_level,
+        b.logger_installed,
+    );
+    Ok(ExitCode::SUCCESS)
+}
+
+fn semver_trick() -> Result<ExitCode, Box<dyn Error>> {
+    SimpleLogger::new()
+        .init()
+        .expect("Failed to initialize logger");
+    let a = log_a();
+    let b = log_b();
+    let c = log_c();
+
+    println!();
+    print_provenance_header();
+    print_provenance(
+        a.crate_name,
+        a.log_version,
+        &a.max_level,
+        a.logger_installed,
+    );
+    print_provenance(
+        b.crate_name,
+        b.log_version,
+        &b.max_level,
+        b.logger_installed,
+    );
+    print_provenance(
+        c.crate_name,
+        c.log_version,
+        &c.max_level,
+        c.logger_installed,
+    );
+    println!();
+    println!(
+        "`c` logged too, but its record was dropped: log {} keeps its own global logger, \
+         which nobody installed. log {} forwards to log {}, where SimpleLogger lives.",
+        c.log_version, b.log_version, a.log_version
+    );
+    Ok(ExitCode::SUCCESS)
+}
+
+fn bridge() -> Result<ExitCode, Box<dyn Error>> {
+    SimpleLogger::new()
+        .init()
+        .expect("Failed to initialize logger");
+    // Neither `a::set_max_level(b::max_level())` nor `a::log_at(b::level(), ..)`
+    // compiles: `b` speaks log 0.3.
+    let filter = b::max_level();
+    let max_level = bridge::level_filter_to_04(filter);
+    a::set_max_level(max_level);
+    let level = b::level();
+    let converted = bridge::level_to_04(level);
+    println!(
+        "b::max_level() = LogLevelFilter::{filter:?} (log {})",
+        b::LOG_VERSION
+    );
+    println!(
+        "converted      = LevelFilter::{max_level:?} (log {})",
+        a::LOG_VERSION
+    );
+    println!(
+        "b::level()     = LogLevel::{level:?} (log {})",
+        b::LOG_VERSION
+    );
+    println!(
+        "converted      = Level::{converted:?} (log {})",
+        a::LOG_VERSION
+    );
+    a::log_at(converted, "logged by a at the level b asked for");
+    Ok(ExitCode::SUCCESS)
+}
+
+fn globals() -> Result<ExitCode, Box<dyn Error>> {
+    // `a` uses plugins 1.0.0 and `b` uses plugins 2.0.0, so there are two
+    // registries in this process and each crate only ever sees its own.
+    a::register_plugin("json");
+    b::register_plugin("yaml");
+    println!("a registered \"json\", b registered \"yaml\"");
+    println!();
+    println!("plugins seen by a: {:?}", a::plugins());
+    println!("plugins seen by b: {:?}", b::plugins());
+    Ok(ExitCode::SUCCESS)
+}
+
+fn macros() -> Result<ExitCode, Box<dyn Error>> {
     SimpleLogger::new()
         .init()
         .expect("Failed to initialize logger");
-    log_a();
-    log_b();
+
+    // `#[macro_use]` puts log 0.3's macros in scope by bare name. Only log
+    // 0.3's `log!` takes a `LogLevel`, so this proves which one it is.
+    log!(
+        log03::LogLevel::Info,
+        "bare log! is log 0.3's, from #[macro_use]"
+    );
+    info!("bare info! is log 0.3's too");
+    // log 0.4's macros are called by path, and name their helpers through
+    // `$crate`, so they work anywhere.
+    log04::info!("log04::info! is log 0.4's");
+    // log 0.3's `info!` expands to a bare `log!`. It only works by path
+    // because `#[macro_use]` put log 0.3's `log!` in scope as well.
+    log03::info!("log03::info! is log 0.3's, but only thanks to #[macro_use]");
+    macros_04::log();
+
+    println!();
+    println!(
+        "Every record reached SimpleLogger, which is a log {} logger. log {} forwards to it.",
+        a::LOG_VERSION,
+        b::LOG_VERSION
+    );
+    Ok(ExitCode::SUCCESS)
+}
+
+mod macros_04 {
+    // A `use` shadows `#[macro_use]`, so in this module the bare names are
+    // log 0.4's. A bare `log!` in scope is also what log 0.3's `info!` calls,
+    // so `log03::info!` no longer compiles here (see `tests/ui`).
+    use log04::{info, log};
+
+    pub fn log() {
+        log!(
+            log04::Level::Info,
+            "bare log! is log 0.4's after `use log04::log`"
+        );
+        info!("bare info! is log 0.4's after `use log04::info`");
+    }
+}
+
+fn print_provenance_header() {
+    println!("crate  log      max level  logger installed");
+}
+
+fn print_provenance(crate_name: &str, log_version: &str, max_level: &str, logger_installed: bool) {
+    println!("{crate_name:<6} {log_version:<8} {max_level:<10} {logger_installed}");
+}
+
+fn duplicates(args: &[String]) -> Result<ExitCode, Box<dyn Error>> {
+    let mut deny = false;
+    let mut path = "Cargo.lock";
+    for arg in args {
+        match arg.as_str() {
+            "--deny" => deny = true,
+            _ => path = arg,
+        }
+    }
+    let lockfile = Lockfile::read(path)?;
+    let duplicates = duplicates::find(&lockfile);
+    for duplicate in &duplicates {
+        println!("{}", duplicate.name);
+        for package in &duplicate.packages {
+            match &package.source {
+                Some(source) => println!("  {} ({source})", package.version),
+                None => println!("  {}", package.version),
+            }
+        }
+    }
+    match duplicates.len() {
+        0 => println!("no package resolves to more than one version"),
+        1 => println!("\n1 package resolves to more than one version"),
+        n => println!("\n{n} packages resolve to more than one version"),
+    }
+    if deny && !duplicates.is_empty() {
+        return Ok(ExitCode::FAILURE);
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn why(args: &[String]) -> Result<ExitCode, Box<dyn Error>> {
+    let mut root = None;
+    let mut path = "Cargo.lock";
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--root" => root = Some(args.next().ok_or("`--root` needs a package name")?),
+            _ => path = arg,
+        }
+    }
+    let lockfile = Lockfile::read(path)?;
+    let graph = Graph::new(&lockfile)?;
+    let roots = match root {
+        Some(name) => vec![graph
+            .find(name, None)
+            .ok_or_else(|| format!("`{name}` is not a single package in {path}"))?],
+        None => graph.roots(),
+    };
+    let duplicates = duplicates::find(&lockfile);
+    // Only packages that are themselves duplicated need their version spelled
+    // out along a path.
+    let label = |id: usize| {
+        let package = graph.package(id);
+        if duplicates.iter().any(|d| d.name == package.name) {
+            package.to_string()
+        } else {
+            package.name.clone()
+        }
+    };
+    for duplicate in &duplicates {
+        for package in &duplicate.packages {
+            println!("{package}");
+            let target = graph
+                .id(package)
+                .expect("duplicates borrow from the same lockfile");
+            for &root in &roots {
+                for route in graph.paths(root, target) {
+                    let route: Vec<String> = route.into_iter().map(label).collect();
+                    println!("  {}", route.join(" → "));
+                }
+            }
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn diff(args: &[String]) -> Result<ExitCode, Box<dyn Error>> {
+    let old_spec = args.first().map_or("HEAD:Cargo.lock", String::as_str);
+    let new_spec = args.get(1).map_or("Cargo.lock", String::as_str);
+    let old = read_lockfile_at(old_spec)?;
+    let new = read_lockfile_at(new_spec)?;
+    let diff = diff::diff(&old, &new);
+    if diff.is_empty() {
+        println!("{old_spec} and {new_spec} lock the same packages");
+        return Ok(ExitCode::SUCCESS);
+    }
+    for package in &diff.added {
+        println!("+ {package}");
+    }
+    for package in &diff.removed {
+        println!("- {package}");
+    }
+    for (old, new) in &diff.bumped {
+        println!("~ {old} → {}", new.version);
+    }
+    let versions = |packages: &[&lockfile::Package]| {
+        let versions: Vec<String> = packages.iter().map(|p| p.version.to_string()).collect();
+        match versions.len() {
+            0 => "(none)".to_string(),
+            _ => versions.join(", "),
+        }
+    };
+    for change in &diff.duplicates {
+        let what = if change.appeared() {
+            "new duplicate"
+        } else if change.resolved() {
+            "resolved duplicate"
+        } else {
+            "changed duplicate"
+        };
+        println!(
+            "\n{what} {}: {} → {}",
+            change.name,
+            versions(&change.old),
+            versions(&change.new)
+        );
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+// Reads a lockfile from disk, or from git when given a `REV:PATH` that isn't
+// a file.
+fn read_lockfile_at(spec: &str) -> Result<Lockfile, Box<dyn Error>> {
+    if Path::new(spec).exists() || !spec.contains(':') {
+        return Ok(Lockfile::read(spec)?);
+    }
+    let output = Command::new("git").args(["show", spec]).output()?;
+    if !output.status.success() {
+        return Err(format!(
+            "`git show {spec}` failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+    Ok(Lockfile::parse(&String::from_utf8(output.stdout)?)?)
+}
+
+fn advise(args: &[String]) -> Result<ExitCode, Box<dyn Error>> {
+    let root = Path::new(args.first().map_or("Cargo.toml", String::as_str));
+    let lockfile = Lockfile::read(root.with_file_name("Cargo.lock"))?;
+    let mut graph = Graph::new(&lockfile)?;
+    let manifests = manifest::workspace(root)?;
+    graph.set_intentional(manifest::dual_edges(&graph, &manifests));
+    let advice = advise::advise(&graph, &manifests);
+    for advice in &advice {
+        println!("{}: unify on {}", advice.name, advice.target.version);
+        for outlier in &advice.outliers {
+            println!("  {} is required by", outlier.package.version);
+            for requirer in &outlier.required_by {
+                match requirer {
+                    Requirer::Local {
+                        package,
+                        manifest,
+                        changes,
+                    } => {
+                        println!("    {} in {}", package.name, manifest.display());
+                        if changes.is_empty() {
+                            println!(
+                                "      (no `{}` entry found; is it inherited from the workspace?)",
+                                advice.name
+                            );
+                        }
+                        for change in changes {
+                            println!("      [{}]", change.section);
+                            println!("      - {}", change.before);
+                            println!("      + {}", change.after);
+                        }
+                    }
+                    Requirer::Upstream { package, used_by } => {
+                        let used_by: Vec<&str> = used_by.iter().map(|p| p.name.as_str()).collect();
+                        println!(
+                            "    {package}, which is not local: upgrade it to a release that \
+                             accepts {} {} (used by {})",
+                            advice.name,
+                            advice.target.version,
+                            used_by.join(", ")
+                        );
+                    }
+                }
+            }
+        }
+    }
+    if advice.is_empty() {
+        println!("no package resolves to more than one version");
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn check(args: &[String]) -> Result<ExitCode, Box<dyn Error>> {
+    let mut policy_path = "duplicates.toml";
+    let mut path = "Cargo.lock";
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--policy" => policy_path = args.next().ok_or("`--policy` needs a file")?,
+            _ => path = arg,
+        }
+    }
+    let policy = Policy::read(policy_path)?;
+    let lockfile = Lockfile::read(path)?;
+    let mut graph = Graph::new(&lockfile)?;
+    // The workspace next to the lockfile, if there is one, says which copies
+    // its packages ask for on purpose.
+    let root = Path::new(path).with_file_name("Cargo.toml");
+    if root.is_file() {
+        let manifests = manifest::workspace(root)?;
+        graph.set_intentional(manifest::dual_edges(&graph, &manifests));
+    }
+    let findings = policy::check(&policy, &graph);
+    for finding in &findings {
+        let versions: Vec<String> = finding
+            .duplicate
+            .packages
+            .iter()
+            .map(|p| p.version.to_string())
+            .collect();
+        let label = match &finding.verdict {
+            Verdict::Allowed => "allowed",
+            Verdict::Warned => "warning",
+            Verdict::Denied | Verdict::NotVia(_) => "denied",
+        };
+        print!(
+            "{label:<8} {} {}",
+            finding.duplicate.name,
+            versions.join(", ")
+        );
+        if !finding.rule.via.is_empty() {
+            print!(" (via {})", finding.rule.via.join(", "));
+        }
+        println!();
+        if let Verdict::NotVia(packages) = &finding.verdict {
+            for package in packages {
+                println!(
+                    "         {package} is reached without going through {}",
+                    finding.rule.via.join(" or ")
+                );
+            }
+        }
+    }
+    let violations = findings.iter().filter(|f| f.verdict.is_violation()).count();
+    match violations {
+        0 => println!("\n{policy_path} allows every duplicate in {path}"),
+        1 => println!("\n1 duplicate breaks {policy_path}"),
+        n => println!("\n{n} duplicates break {policy_path}"),
+    }
+    if violations > 0 {
+        return Ok(ExitCode::FAILURE);
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn graph(args: &[String]) -> Result<ExitCode, Box<dyn Error>> {
+    let mut mermaid = false;
+    let mut duplicates_only = false;
+    let mut path = "Cargo.lock";
+    for arg in args {
+        match arg.as_str() {
+            "--mermaid" => mermaid = true,
+            "--duplicates" => duplicates_only = true,
+            _ => path = arg,
+        }
+    }
+    let lockfile = Lockfile::read(path)?;
+    let graph = Graph::new(&lockfile)?;
+    let view = if duplicates_only {
+        View::duplicates_only(&graph)
+    } else {
+        View::full(&graph)
+    };
+    if mermaid {
+        print!("{}", export::mermaid(&graph, &view));
+    } else {
+        print!("{}", export::dot(&graph, &view));
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn sbom(args: &[String]) -> Result<ExitCode, Box<dyn Error>> {
+    let mut spdx = false;
+    let mut root = None;
+    let mut manifest_path = "Cargo.toml";
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--spdx" => spdx = true,
+            "--root" => root = Some(args.next().ok_or("`--root` needs a package name")?),
+            _ => manifest_path = arg,
+        }
+    }
+    let manifest_path = Path::new(manifest_path);
+    let lockfile = Lockfile::read(manifest_path.with_file_name("Cargo.lock"))?;
+    let graph = Graph::new(&lockfile)?;
+    let manifests = manifest::workspace(manifest_path)?;
+    let name = match root {
+        Some(name) => name.as_str(),
+        None => manifests
+            .first()
+            .and_then(|m| m.name.as_deref())
+            .ok_or("the workspace root has no package; pick one with `--root`")?,
+    };
+    let root = graph
+        .find(name, None)
+        .ok_or_else(|| format!("`{name}` is not a single package in Cargo.lock"))?;
+    let sbom = Sbom::new(&graph, root, &manifests);
+    if spdx {
+        print!("{}", sbom::spdx(&sbom, std::time::SystemTime::now()));
+    } else {
+        println!("{:#}", sbom::cyclonedx(&sbom));
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn resolve(args: &[String]) -> Result<ExitCode, Box<dyn Error>> {
+    let strategies: Vec<&dyn Strategy> = match args.first().map(String::as_str) {
+        None => vec![&resolve::Cargo, &resolve::Pip, &resolve::Npm],
+        Some("cargo") => vec![&resolve::Cargo],
+        Some("pip") => vec![&resolve::Pip],
+        Some("npm") => vec![&resolve::Npm],
+        Some(other) => return Err(format!("unknown strategy `{other}`").into()),
+    };
+    let (index, root) = demo_index()?;
+    let mut failed = false;
+    for (i, strategy) in strategies.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        println!("{}", strategy.name());
+        match strategy.resolve(&index, &root) {
+            Ok(resolution) => print_resolution(&resolution),
+            Err(conflict) => {
+                println!("  no solution: {conflict}");
+                failed = true;
+            }
+        }
+    }
+    // On its own, a strategy that fails is an error; next to the others it
+    // is the point.
+    Ok(if failed && strategies.len() == 1 {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
+fn pip_check(args: &[String]) -> Result<ExitCode, Box<dyn Error>> {
+    let path = Path::new(args.first().map_or("Cargo.toml", String::as_str));
+    let lockfile = Lockfile::read(path.with_file_name("Cargo.lock"))?;
+    let mut graph = Graph::new(&lockfile)?;
+    let manifests = manifest::workspace(path)?;
+    // The root and `bridge` depend on both log versions on purpose, which pip
+    // could never install. The index leaves those out to get to `a` and `b`.
+    graph.set_intentional(manifest::dual_edges(&graph, &manifests));
+    let index = Index::locked(&graph, &manifests)?;
+    let root = manifests
+        .first()
+        .and_then(|m| m.name.as_deref().zip(Some(&m.version)))
+        .and_then(|(name, version)| index.releases(name).iter().find(|r| r.version == *version))
+        .ok_or_else(|| format!("{} is not a package", path.display()))?;
+    match resolve::Pip.resolve(&index, &root.dependencies) {
+        Ok(resolution) => {
+            println!(
+                "one version per name works: {} packages",
+                resolution.packages.len()
+            );
+            Ok(ExitCode::SUCCESS)
+        }
+        Err(conflict) => {
+            print!(
+                "{}",
+                explain::explain(&index, &root.dependencies, &conflict)
+            );
+            Ok(ExitCode::FAILURE)
+        }
+    }
+}
+
+fn ecosystems(args: &[String]) -> Result<ExitCode, Box<dyn Error>> {
+    let mut cargo = "Cargo.lock";
+    let mut npm = "fixtures/npm/package-lock.json";
+    let mut requirements = "fixtures/pip/requirements.txt";
+    let mut index = "fixtures/pip/index.toml";
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .map(String::as_str)
+                .ok_or_else(|| format!("`{arg}` needs a value"))
+        };
+        match arg.as_str() {
+            "--cargo" => cargo = value()?,
+            "--npm" => npm = value()?,
+            "--pip" => {
+                requirements = value()?;
+                index = value()?;
+            }
+            other => return Err(format!("unexpected argument `{other}`").into()),
+        }
+    }
+
+    println!("cargo: {cargo}");
+    print_accepted(&Lockfile::read(cargo)?);
+    println!();
+    println!("npm: {npm}");
+    print_accepted(&npm::read(npm)?);
+    println!();
+    println!("pip: {requirements} against {index}");
+    let root = pip::read_requirements(requirements)?;
+    let index = pip::read_index(index)?;
+    match resolve::Pip.resolve(&index, &root) {
+        Ok(resolution) => print_accepted(&pip::lockfile(&resolution)),
+        Err(conflict) => {
+            println!("  rejected:");
+            for line in explain::explain(&index, &root, &conflict)
+                .to_string()
+                .lines()
+            {
+                if line.is_empty() {
+                    println!();
+                } else {
+                    println!("    {line}");
+                }
+            }
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn print_accepted(lockfile: &Lockfile) {
+    println!("  accepted: {} packages", lockfile.packages.len());
+    for duplicate in duplicates::find(lockfile) {
+        let versions: Vec<String> = duplicate
+            .packages
+            .iter()
+            .map(|p| p.version.to_string())
+            .collect();
+        println!("    {} {}", duplicate.name, versions.join(", "));
+    }
+}
+
+// The workspace's `a`/`b`/`log` graph, with a few more `log` releases to pick
+// from. log 0.3.9 is the semver-trick release that depends on log 0.4.
+fn demo_index() -> Result<(Index, Vec<Dependency>), Box<dyn Error>> {
+    let dependency = |name: &str, req: &str| -> Result<Dependency, Box<dyn Error>> {
+        Ok(Dependency::new(name, req.parse()?))
+    };
+    let mut index = Index::new();
+    index.add(
+        "a",
+        Version::new(0, 1, 0),
+        vec![dependency("log", "0.4.22")?],
+    );
+    index.add(
+        "b",
+        Version::new(0, 1, 0),
+        vec![dependency("log", "0.3.9")?],
+    );
+    index.add("log", Version::new(0, 3, 8), Vec::new());
+    index.add(
+        "log",
+        Version::new(0, 3, 9),
+        vec![dependency("log", "0.4")?],
+    );
+    index.add("log", Version::new(0, 4, 0), Vec::new());
+    index.add("log", Version::new(0, 4, 22), Vec::new());
+    let root = vec![dependency("a", "0.1")?, dependency("b", "0.1")?];
+    Ok((index, root))
+}
+
+fn print_resolution(resolution: &Resolution) {
+    let mut packages: Vec<_> = resolution.packages.iter().collect();
+    packages.sort_by(|a, b| (a.path(), &a.version).cmp(&(b.path(), &b.version)));
+    let nested = packages.iter().any(|p| !p.location.is_empty());
+    for package in &packages {
+        if nested {
+            println!("  {} {}", package.path(), package.version);
+        } else {
+            println!("  {} {}", package.name, package.version);
+        }
+    }
+    let mut names: Vec<&str> = packages.iter().map(|p| p.name.as_str()).collect();
+    names.dedup();
+    for name in names {
+        let copies = resolution.copies(name);
+        if copies.len() > 1 {
+            let versions: Vec<String> = copies.iter().map(|p| p.version.to_string()).collect();
+            println!(
+                "  {} copies of {name}: {}",
+                copies.len(),
+                versions.join(", ")
+            );
+        }
+    }
+}
+
+fn symbols(args: &[String]) -> Result<ExitCode, Box<dyn Error>> {
+    let mut filter = None;
+    let mut binary = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--crate" => filter = Some(args.next().ok_or("`--crate` needs a crate name")?),
+            _ => binary = Some(PathBuf::from(arg)),
+        }
+    }
+    // Only v0 symbol names tell two copies of a crate apart, so build this
+    // binary once more with them, away from the normal build.
+    let binary = match binary {
+        Some(path) => path,
+        None => symbols::build(
+            Path::new("."),
+            env!("CARGO_PKG_NAME"),
+            Path::new("target/symbols"),
+        )?,
+    };
+    let crates = symbols::by_crate(&symbols::read(&binary)?);
+    if crates.iter().all(|c| c.krate.disambiguator.is_none()) {
+        println!(
+            "note: {} has no v0-mangled symbols, so copies of a crate can't be told apart.\n\
+             Rebuild with RUSTFLAGS=\"-C symbol-mangling-version=v0\".\n",
+            binary.display()
+        );
+    }
+    // Cargo keeps the rlibs that went into a binary in `deps` next to it.
+    let deps = binary.with_file_name("deps");
+    let versions = if deps.is_dir() {
+        symbols::versions(&deps, env::current_dir()?)?
+    } else {
+        BTreeMap::new()
+    };
+    let version = |code: &symbols::CrateCode| {
+        versions
+            .get(&code.krate)
+            .map_or_else(|| "-".to_string(), Version::to_string)
+    };
+
+    println!(
+        "{:<24} {:<8} {:<18} {:>8} {:>11}",
+        "crate", "version", "disambiguator", "symbols", "code bytes"
+    );
+    for code in crates
+        .iter()
+        .filter(|c| filter.is_none_or(|name| &c.krate.name == name))
+    {
+        let disambiguator = code
+            .krate
+            .disambiguator
+            .map_or_else(|| "-".to_string(), |d| format!("{d:016x}"));
+        println!(
+            "{:<24} {:<8} {:<18} {:>8} {:>11}",
+            code.krate.name,
+            version(code),
+            disambiguator,
+            code.symbols.len(),
+            code.code_bytes
+        );
+        if filter.is_some() {
+            for path in &code.symbols {
+                println!("    {path}");
+            }
+        }
+    }
+
+    let mut copies: Vec<(&str, Vec<&symbols::CrateCode>)> = Vec::new();
+    for code in &crates {
+        match copies.last_mut() {
+            Some((name, codes)) if *name == code.krate.name => codes.push(code),
+            _ => copies.push((&code.krate.name, vec![code])),
+        }
+    }
+    println!();
+    for (name, codes) in copies
+        .iter()
+        .filter(|(name, codes)| codes.len() > 1 && filter.is_none_or(|f| f == name))
+    {
+        let bytes: Vec<String> = codes
+            .iter()
+            .map(|code| format!("{} bytes ({})", code.code_bytes, version(code)))
+            .collect();
+        println!(
+            "{} distinct `{name}` crates contributed code: {}",
+            bytes.len(),
+            bytes.join(", ")
+        );
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn artifacts(args: &[String]) -> Result<ExitCode, Box<dyn Error>> {
+    let deps_dir = args.first().map_or("target/debug/deps", String::as_str);
+    // Cargo writes source paths relative to the workspace root it ran in.
+    let artifacts = depinfo::artifacts(deps_dir, env::current_dir()?)?;
+    let duplicates = depinfo::duplicates(&artifacts);
+    for duplicate in &duplicates {
+        println!("{}", duplicate.name);
+        for source in &duplicate.sources {
+            match source.version {
+                Some(version) => println!("  {version} {}", source.dir.display()),
+                None => println!("  {}", source.dir.display()),
+            }
+            for artifact in &source.artifacts {
+                println!("    {} ({})", artifact.hash, artifact.outputs.join(", "));
+            }
+        }
+    }
+    match duplicates.len() {
+        0 => println!("no crate was compiled from more than one source"),
+        1 => println!("\n1 crate was compiled from more than one source"),
+        n => println!("\n{n} crates were compiled from more than one source"),
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn size_cost(args: &[String]) -> Result<ExitCode, Box<dyn Error>> {
+    let scratch = Path::new(args.first().map_or("target/size-cost", String::as_str));
+    let workspace = env::current_dir()?;
+    let mut builds = Vec::new();
+    // Same-length directory names keep any embedded paths the same size.
+    for (dir, log) in [("split", cost::SPLIT), ("unify", cost::UNIFIED)] {
+        let dir = scratch.join(dir);
+        println!(
+            "building {} with {} on log {log}",
+            dir.display(),
+            cost::CRATE
+        );
+        cost::copy_workspace(&workspace, &dir)?;
+        if log == cost::UNIFIED {
+            cost::unify(&dir)?;
+        }
+        builds.push(cost::build(&dir, env!("CARGO_PKG_NAME"))?);
+    }
+    let (split, unified) = (&builds[0], &builds[1]);
+
+    let (split_label, unified_label) = (
+        format!("{} on log {}", cost::CRATE, cost::SPLIT),
+        format!("{} on log {}", cost::CRATE, cost::UNIFIED),
+    );
+    println!();
+    println!(
+        "{:<26} {split_label:>16} {unified_label:>16} {:>10}",
+        "", "difference"
+    );
+    let row = |label: &str, split: u64, unified: u64| {
+        println!(
+            "{label:<26} {split:>16} {unified:>16} {:>+10}",
+            split as i64 - unified as i64
+        );
+    };
+    row(
+        "executable bytes",
+        split.executable_bytes,
+        unified.executable_bytes,
+    );
+    row("rlib bytes", split.rlib_bytes(), unified.rlib_bytes());
+    row(
+        "codegen units",
+        split.codegen_units() as u64,
+        unified.codegen_units() as u64,
+    );
+
+    let mut keys: Vec<_> = split
+        .crates
+        .iter()
+        .chain(&unified.crates)
+        .map(|c| (&c.name, c.version.as_ref()))
+        .collect();
+    keys.sort();
+    keys.dedup();
+    let cell = |compiled: Option<&cost::Compiled>| {
+        compiled.map_or_else(
+            || "-".to_string(),
+            |c| format!("{} / {}", c.rlib_bytes, c.codegen_units),
+        )
+    };
+    let mut changed = Vec::new();
+    let mut same = 0;
+    for (name, version) in keys {
+        let (before, after) = (split.find(name, version), unified.find(name, version));
+        if before == after {
+            same += 1;
+            continue;
+        }
+        let label = match version {
+            Some(version) => format!("{name} {version}"),
+            None => name.clone(),
+        };
+        changed.push((label, cell(before), cell(after)));
+    }
+    let width = changed
+        .iter()
+        .map(|(label, ..)| label.len())
+        .fold(26, usize::max);
+    println!();
+    println!(
+        "{:<width$} {split_label:>16} {unified_label:>16}",
+        "rlib bytes / codegen units"
+    );
+    for (label, before, after) in changed {
+        println!("{label:<width$} {before:>16} {after:>16}");
+    }
+    println!("({same} other crates are identical in both builds)");
+    Ok(ExitCode::SUCCESS)
+}
+
+fn features(args: &[String]) -> Result<ExitCode, Box<dyn Error>> {
+    let scratch = Path::new(args.first().map_or("target/features", String::as_str));
+    for resolver in features::RESOLVERS {
+        let dir = scratch.join(format!("resolver-{resolver}"));
+        features::write(&dir, resolver)?;
+        println!("resolver = \"{resolver}\"");
+        for seen in features::build(&dir)? {
+            println!("  {:<18} {}", seen.by, seen.features.join(", "));
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn links() -> Result<ExitCode, Box<dyn Error>> {
+    let dir = Path::new("fixtures/links");
+    let manifest = dir.join("Cargo.toml");
+    let stderr = match cargo::run(dir, &["generate-lockfile", "--offline"]) {
+        Ok(_) => {
+            println!(
+                "{} resolved, so nothing links `greet` twice",
+                manifest.display()
+            );
+            return Ok(ExitCode::FAILURE);
+        }
+        Err(cargo::Error::Failed { stderr, .. }) => stderr,
+        Err(e) => return Err(e.into()),
+    };
+    println!(
+        "Cargo refuses to resolve {}, where a needs greet-sys 1.0.0 and b needs greet-sys 2.0.0:",
+        manifest.display()
+    );
+    println!();
+    print!("{stderr}");
+    Ok(ExitCode::SUCCESS)
+}
+
+fn registry(args: &[String]) -> Result<ExitCode, Box<dyn Error>> {
+    let dir = Path::new("target/local-registry");
+    let releases = registry::publish(Path::new(registry::DIR), dir)?;
+    match args.first().map(String::as_str) {
+        None => {}
+        Some("publish") => {
+            for release in releases {
+                let yanked = if release.yanked { " (yanked)" } else { "" };
+                println!(
+                    "published {} {}{yanked}  {}",
+                    release.name, release.version, release.checksum
+                );
+            }
+            return Ok(ExitCode::SUCCESS);
+        }
+        Some(other) => return Err(format!("unknown registry command `{other}`").into()),
+    }
+
+    let cases: [&[(&str, &str)]; 5] = [
+        &[("a", "0.4.22"), ("b", "0.4.10")],
+        &[("b", "0.4.10")],
+        &[("a", "0.4.22"), ("b", "0.3")],
+        &[("a", "0.4.22"), ("b", "=0.4.10")],
+        &[("b", "=0.4.23")],
+    ];
+    let scratch = Path::new("target/registry");
+    for consumers in cases {
+        let wants: Vec<String> = consumers
+            .iter()
+            .map(|(name, req)| format!("{name} wants log {req}"))
+            .collect();
+        println!("{}", wants.join(", "));
+        registry::write_consumers(scratch, consumers)?;
+        match registry::run(dir, scratch) {
+            Ok(versions) => {
+                for (name, version) in versions {
+                    println!("  {name} got log {version}");
+                }
+            }
+            Err(registry::Error::Cargo(cargo::Error::Failed { stderr, .. })) => {
+                for line in stderr.trim_end().lines() {
+                    if line.is_empty() {
+                        println!();
+                    } else {
+                        println!("  {line}");
+                    }
+                }
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn scenario(args: &[String]) -> Result<ExitCode, Box<dyn Error>> {
+    let mut depth = 1;
+    let mut dir = Path::new("target/scenario");
+    let mut versions: Vec<Version> = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--depth" => {
+                depth = args
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .filter(|&n| n > 0)
+                    .ok_or("`--depth` needs a positive number")?;
+            }
+            "--dir" => dir = Path::new(args.next().ok_or("`--dir` needs a directory")?),
+            version => versions.push(version.parse()?),
+        }
+    }
+    if versions.is_empty() {
+        return Err("give at least one version of the shared crate, e.g. `0.1.0 1.0.0`".into());
+    }
+
+    let scenario = Scenario::deep(depth, &versions);
+    println!(
+        "building {} with {} chain(s) of depth {depth}",
+        dir.display(),
+        scenario.chains.len()
+    );
+    scenario.write(dir)?;
+    let outcome = scenario::build(dir)?;
+    for report in &outcome.reports {
+        println!(
+            "  {} reached {} {} (copy {})",
+            report.leaf,
+            scenario::SHARED,
+            report.version,
+            report.copy
+        );
+    }
+    let locked: Vec<String> = outcome.locked.iter().map(ToString::to_string).collect();
+    let copies = outcome.copies();
+    println!(
+        "Cargo.lock has {} {}: {}; {copies} {} at run time",
+        locked.len(),
+        scenario::SHARED,
+        locked.join(", "),
+        if copies == 1 { "copy" } else { "copies" }
+    );
+    Ok(ExitCode::SUCCESS)
+}
+
+fn compile_cost(args: &[String]) -> Result<ExitCode, Box<dyn Error>> {
+    let units = match args.first() {
+        Some(report) => timings::read(report)?,
+        None => {
+            let dir = Path::new("target/compile-cost");
+            println!(
+                "building a copy of this workspace in {} with --timings",
+                dir.display()
+            );
+            cost::copy_workspace(&env::current_dir()?, dir)?;
+            timings::build(dir)?
+        }
+    };
+    let duplicates = timings::duplicates(&units);
+    for duplicate in &duplicates {
+        println!();
+        println!(
+            "{:<26} {:>6} {:>10} {:>12}",
+            duplicate.name, "units", "compile s", "wall-clock s"
+        );
+        for copy in &duplicate.copies {
+            println!(
+                "  {:<24} {:>6} {:>10.2} {:>12.2}",
+                copy.version.to_string(),
+                copy.units,
+                copy.seconds,
+                copy.wall_clock
+            );
+        }
+        println!(
+            "  {:<24} {:>6} {:>+10.2} {:>+12.2}",
+            "extra (all but newest)",
+            "",
+            duplicate.extra_seconds(),
+            duplicate.extra_wall_clock()
+        );
+    }
+
+    let compile: f64 = units.iter().map(|u| u.duration).sum();
+    let extra_compile: f64 = duplicates
+        .iter()
+        .map(timings::Duplicate::extra_seconds)
+        .sum();
+    let extra_wall_clock: f64 = duplicates
+        .iter()
+        .map(timings::Duplicate::extra_wall_clock)
+        .sum();
+    println!();
+    match duplicates.len() {
+        0 => println!("no crate was compiled in more than one version"),
+        n => println!(
+            "{n} duplicated {} cost {extra_compile:.2}s of {compile:.2}s compile time \
+             and {extra_wall_clock:.2}s of {:.2}s wall-clock time",
+            if n == 1 { "crate" } else { "crates" },
+            timings::wall_clock(&units)
+        ),
+    }
+    Ok(ExitCode::SUCCESS)
 }
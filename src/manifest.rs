@@ -0,0 +1,306 @@
+//! The `Cargo.toml` files of a workspace: its members and every path
+//! dependency they reach, with the dependencies each one declares.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+use crate::graph::Graph;
+use crate::semver::{Version, VersionReq};
+
+#[derive(Debug)]
+pub enum Error {
+    Io(PathBuf, io::Error),
+    Toml(PathBuf, toml::de::Error),
+    Invalid(PathBuf, String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(path, e) => write!(f, "failed to read {}: {e}", path.display()),
+            Error::Toml(path, e) => write!(f, "failed to parse {}: {e}", path.display()),
+            Error::Invalid(path, message) => write!(f, "invalid {}: {message}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(_, e) => Some(e),
+            Error::Toml(_, e) => Some(e),
+            Error::Invalid(..) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Manifest {
+    pub path: PathBuf,
+    /// `None` for a virtual workspace root.
+    pub name: Option<String>,
+    /// Cargo treats a package without a version as 0.0.0.
+    pub version: Version,
+    /// `workspace.members` as written.
+    pub members: Vec<String>,
+    pub dependencies: Vec<Dependency>,
+}
+
+impl Manifest {
+    pub fn dir(&self) -> &Path {
+        self.path
+            .parent()
+            .expect("manifest paths end in Cargo.toml")
+    }
+
+    /// The entries that share their table with another entry for the same
+    /// package, told apart by renaming, like `log03` and `log04` for `log`.
+    /// Such a crate depends on several versions on purpose.
+    pub fn dual_dependencies(&self) -> Vec<&Dependency> {
+        self.dependencies
+            .iter()
+            .filter(|d| {
+                self.dependencies
+                    .iter()
+                    .filter(|other| other.section == d.section && other.package == d.package)
+                    .count()
+                    > 1
+            })
+            .collect()
+    }
+}
+
+/// One entry of a `[dependencies]`-like table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dependency {
+    /// The table it is declared in, e.g. `dependencies` or
+    /// `target.'cfg(unix)'.dev-dependencies`.
+    pub section: String,
+    /// The name it is declared under, which differs from `package` when it
+    /// is renamed.
+    pub key: String,
+    pub package: String,
+    pub req: Option<String>,
+    /// The dependency's manifest directory, for path dependencies.
+    pub path: Option<PathBuf>,
+    /// The entry as written.
+    pub value: toml::Value,
+}
+
+const SECTIONS: [&str; 3] = ["dependencies", "dev-dependencies", "build-dependencies"];
+
+pub fn read(path: impl AsRef<Path>) -> Result<Manifest, Error> {
+    let path = path.as_ref();
+    let input = fs::read_to_string(path).map_err(|e| Error::Io(path.to_path_buf(), e))?;
+    parse(path, &input)
+}
+
+/// Parses the manifest at `path` from `input`. Relative dependency paths are
+/// resolved against `path`'s directory.
+pub fn parse(path: &Path, input: &str) -> Result<Manifest, Error> {
+    let invalid = |message: String| Error::Invalid(path.to_path_buf(), message);
+    let document =
+        toml::from_str::<toml::Table>(input).map_err(|e| Error::Toml(path.to_path_buf(), e))?;
+    let package = document.get("package").and_then(toml::Value::as_table);
+    let name = package
+        .and_then(|p| p.get("name"))
+        .and_then(toml::Value::as_str)
+        .map(String::from);
+    let version = match package
+        .and_then(|p| p.get("version"))
+        .and_then(toml::Value::as_str)
+    {
+        Some(version) => version.parse().map_err(|e| invalid(format!("{e}")))?,
+        None => Version::new(0, 0, 0),
+    };
+
+    let members = match document
+        .get("workspace")
+        .and_then(toml::Value::as_table)
+        .and_then(|w| w.get("members"))
+    {
+        None => Vec::new(),
+        Some(members) => members
+            .as_array()
+            .and_then(|m| m.iter().map(|m| m.as_str().map(String::from)).collect())
+            .ok_or_else(|| invalid("`workspace.members` is not an array of strings".into()))?,
+    };
+
+    let mut tables = Vec::new();
+    for section in SECTIONS {
+        if let Some(table) = document.get(section) {
+            tables.push((section.to_string(), table));
+        }
+    }
+    if let Some(targets) = document.get("target").and_then(toml::Value::as_table) {
+        for (cfg, target) in targets {
+            for section in SECTIONS {
+                if let Some(table) = target.as_table().and_then(|t| t.get(section)) {
+                    tables.push((format!("target.'{cfg}'.{section}"), table));
+                }
+            }
+        }
+    }
+
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let mut dependencies = Vec::new();
+    for (section, table) in tables {
+        let table = table
+            .as_table()
+            .ok_or_else(|| invalid(format!("`{section}` is not a table")))?;
+        for (key, value) in table {
+            let (package, req, dep_path) = match value {
+                toml::Value::String(req) => (key.as_str(), Some(req.as_str()), None),
+                toml::Value::Table(entry) => {
+                    let string = |k: &str| entry.get(k).and_then(toml::Value::as_str);
+                    (
+                        string("package").unwrap_or(key),
+                        string("version"),
+                        string("path"),
+                    )
+                }
+                _ => return Err(invalid(format!("`{section}.{key}` is malformed"))),
+            };
+            dependencies.push(Dependency {
+                section: section.clone(),
+                key: key.clone(),
+                package: package.to_string(),
+                req: req.map(String::from),
+                path: dep_path.map(|p| normalize(&dir.join(p))),
+                value: value.clone(),
+            });
+        }
+    }
+    Ok(Manifest {
+        path: path.to_path_buf(),
+        name,
+        version,
+        members,
+        dependencies,
+    })
+}
+
+/// The edges from each local package to what its
+/// [dual dependencies](Manifest::dual_dependencies) resolved to, as
+/// `(dependent, dependency)` indices into `graph`, for
+/// [`Graph::set_intentional`]. The package asked for every one of those
+/// versions.
+pub fn dual_edges(graph: &Graph<'_>, manifests: &[Manifest]) -> Vec<(usize, usize)> {
+    let mut edges = Vec::new();
+    for manifest in manifests {
+        let dual = manifest.dual_dependencies();
+        if dual.is_empty() {
+            continue;
+        }
+        let Some(id) = (0..graph.len()).find(|&id| {
+            let package = graph.package(id);
+            package.source.is_none()
+                && manifest.name.as_deref() == Some(&package.name)
+                && package.version == manifest.version
+        }) else {
+            continue;
+        };
+        for &dep in graph.dependencies(id) {
+            let package = graph.package(dep);
+            let asked = dual.iter().any(|d| {
+                d.package == package.name
+                    && d.req
+                        .as_deref()
+                        .and_then(|req| req.parse::<VersionReq>().ok())
+                        .is_none_or(|req| req.matches(&package.version))
+            });
+            if asked {
+                edges.push((id, dep));
+            }
+        }
+    }
+    edges
+}
+
+/// Reads the workspace rooted at `root` (a `Cargo.toml`): the root itself,
+/// its `members` (a trailing `/*` is expanded) and every path dependency
+/// reachable from them, each read once.
+pub fn workspace(root: impl AsRef<Path>) -> Result<Vec<Manifest>, Error> {
+    let root = normalize(root.as_ref());
+    let mut queue = VecDeque::from([root.clone()]);
+    let mut manifests: Vec<Manifest> = Vec::new();
+    while let Some(path) = queue.pop_front() {
+        if manifests.iter().any(|m| m.path == path) {
+            continue;
+        }
+        let manifest = read(&path)?;
+        if path == root {
+            for member in members(&manifest)? {
+                queue.push_back(member.join("Cargo.toml"));
+            }
+        }
+        for dependency in &manifest.dependencies {
+            if let Some(dir) = &dependency.path {
+                queue.push_back(dir.join("Cargo.toml"));
+            }
+        }
+        manifests.push(manifest);
+    }
+    Ok(manifests)
+}
+
+fn members(root: &Manifest) -> Result<Vec<PathBuf>, Error> {
+    let dir = root.dir();
+    let mut dirs = Vec::new();
+    for member in &root.members {
+        match member.strip_suffix("/*") {
+            Some(parent) => {
+                let parent = dir.join(parent);
+                let entries = fs::read_dir(&parent).map_err(|e| Error::Io(parent.clone(), e))?;
+                for entry in entries {
+                    let entry = entry.map_err(|e| Error::Io(parent.clone(), e))?.path();
+                    if entry.join("Cargo.toml").is_file() {
+                        dirs.push(normalize(&entry));
+                    }
+                }
+            }
+            None => dirs.push(normalize(&dir.join(member))),
+        }
+    }
+    dirs.sort();
+    Ok(dirs)
+}
+
+/// Removes `.` and folds `..` into the preceding component without touching
+/// the file system, so `a/../fixtures/x` becomes `fixtures/x`.
+pub fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => match out.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    out.pop();
+                }
+                Some(Component::RootDir | Component::Prefix(_)) => {}
+                _ => out.push(".."),
+            },
+            _ => out.push(component),
+        }
+    }
+    out
+}
+
+/// The path to `to` as seen from the directory `from`, both as returned by
+/// [`normalize`].
+pub fn relative(from: &Path, to: &Path) -> PathBuf {
+    let from: Vec<Component> = from.components().collect();
+    let to: Vec<Component> = to.components().collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    let mut out = PathBuf::new();
+    for _ in common..from.len() {
+        out.push("..");
+    }
+    for component in &to[common..] {
+        out.push(component);
+    }
+    out
+}
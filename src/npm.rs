@@ -0,0 +1,174 @@
+//! Reads an npm `package-lock.json` (lockfile version 2 or 3) into the same
+//! [`Lockfile`] model as `Cargo.lock`, so the duplicate reports work on it.
+//!
+//! npm installs a package once per `node_modules` directory that needs it,
+//! so each entry's install path, e.g.
+//! `node_modules/request/node_modules/har-validator`, becomes its source.
+//! That keeps two copies of the same version apart, just as they are on
+//! disk.
+
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+use serde_json::{Map, Value};
+
+use crate::lockfile::{Lockfile, Package};
+
+type Object = Map<String, Value>;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Json(serde_json::Error),
+    Invalid(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "failed to read package-lock.json: {e}"),
+            Error::Json(e) => write!(f, "failed to parse package-lock.json: {e}"),
+            Error::Invalid(message) => write!(f, "invalid package-lock.json: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Json(e) => Some(e),
+            Error::Invalid(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::Json(e)
+    }
+}
+
+pub fn read(path: impl AsRef<Path>) -> Result<Lockfile, Error> {
+    parse(&std::fs::read_to_string(path)?)
+}
+
+/// The project itself is the package without a source. Each dependency is
+/// linked to the copy Node.js would load: the first one found walking up
+/// from the dependent's own `node_modules`.
+pub fn parse(input: &str) -> Result<Lockfile, Error> {
+    let document = serde_json::from_str::<Value>(input)?;
+    let version = document
+        .as_object()
+        .and_then(|d| d.get("lockfileVersion"))
+        .and_then(Value::as_f64)
+        .ok_or_else(|| Error::Invalid("no `lockfileVersion`".into()))?;
+    if version < 2.0 {
+        return Err(Error::Invalid(format!(
+            "lockfile version {version} has no `packages` map; run `npm install` with npm 7 or later"
+        )));
+    }
+    let entries = document
+        .as_object()
+        .and_then(|d| d.get("packages"))
+        .and_then(Value::as_object)
+        .ok_or_else(|| Error::Invalid("`packages` is not an object".into()))?;
+
+    let mut packages = Vec::new();
+    for (path, entry) in entries {
+        let entry = entry
+            .as_object()
+            .ok_or_else(|| Error::Invalid(format!("`{path}` is not an object")))?;
+        // Workspace links point at another entry, which is listed itself.
+        if entry.get("link").and_then(Value::as_bool) == Some(true) {
+            continue;
+        }
+        let string = |key: &str| entry.get(key).and_then(Value::as_str);
+        let name = match path.rsplit_once("node_modules/") {
+            Some((_, name)) => name,
+            None => string("name").unwrap_or("root"),
+        };
+        let version = string("version")
+            .unwrap_or("0.0.0")
+            .parse()
+            .map_err(|e| Error::Invalid(format!("`{path}`: {e}")))?;
+
+        let mut dependencies = Vec::new();
+        let mut sections = vec![("dependencies", false), ("optionalDependencies", true)];
+        if path.is_empty() {
+            sections.push(("devDependencies", false));
+        }
+        for (section, optional) in sections {
+            let Some(deps) = entry.get(section).and_then(Value::as_object) else {
+                continue;
+            };
+            for dep in deps.keys() {
+                match installed(entries, path, dep) {
+                    Some(found) => dependencies.push(reference(entries, found)?),
+                    None if optional => {}
+                    None => {
+                        let by = if path.is_empty() {
+                            "the project".to_string()
+                        } else {
+                            format!("`{path}`")
+                        };
+                        return Err(Error::Invalid(format!(
+                            "`{dep}`, needed by {by}, is not installed"
+                        )));
+                    }
+                }
+            }
+        }
+        packages.push(Package {
+            name: name.to_string(),
+            version,
+            source: (!path.is_empty()).then(|| path.clone()),
+            checksum: string("integrity").map(String::from),
+            dependencies,
+        });
+    }
+    Ok(Lockfile {
+        version: Some(version as i64),
+        packages,
+    })
+}
+
+// The install path Node.js resolves `name` to from the package at `from`.
+fn installed<'a>(entries: &'a Object, from: &str, name: &str) -> Option<&'a str> {
+    let mut dir = from;
+    loop {
+        let candidate = if dir.is_empty() {
+            format!("node_modules/{name}")
+        } else {
+            format!("{dir}/node_modules/{name}")
+        };
+        if let Some((path, _)) = entries.get_key_value(&candidate) {
+            return Some(path);
+        }
+        if dir.is_empty() {
+            return None;
+        }
+        dir = dir
+            .rsplit_once("/node_modules/")
+            .map_or("", |(parent, _)| parent);
+    }
+}
+
+// The fully qualified `name version (source)` form, which never needs
+// disambiguating.
+fn reference(entries: &Object, path: &str) -> Result<String, Error> {
+    let name = path.rsplit_once("node_modules/").map_or(path, |(_, n)| n);
+    let version = entries[path]
+        .as_object()
+        .and_then(|e| e.get("version"))
+        .and_then(Value::as_str)
+        .ok_or_else(|| Error::Invalid(format!("`{path}` has no version")))?;
+    Ok(format!("{name} {version} ({path})"))
+}
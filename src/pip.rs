@@ -0,0 +1,254 @@
+//! Loads a `requirements.txt` and a snapshot of the package index it is
+//! installed from into the resolver's model, so the one-version-per-name
+//! strategy can be run on a Python project without a network. The snapshot
+//! is a TOML list of releases:
+//!
+//! ```toml
+//! [[release]]
+//! name = "Flask"
+//! version = "2.0.0"
+//! requires-dist = ["Werkzeug>=2.0", "Jinja2>=3.0"]
+//! ```
+//!
+//! PEP 440 specifiers are translated to SemVer requirements. `!=` and `===`
+//! have no equivalent and are rejected.
+//!
+//! A successful resolution can be turned into a [`Lockfile`] with
+//! [`lockfile`], so the same reports run on it as on a `Cargo.lock`.
+
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+use crate::lockfile::{Lockfile, Package};
+use crate::resolve::{Dependency, Index, Resolution};
+use crate::semver::{Version, VersionReq};
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Toml(toml::de::Error),
+    Invalid(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "failed to read: {e}"),
+            Error::Toml(e) => write!(f, "failed to parse index snapshot: {e}"),
+            Error::Invalid(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Toml(e) => Some(e),
+            Error::Invalid(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<toml::de::Error> for Error {
+    fn from(e: toml::de::Error) -> Self {
+        Error::Toml(e)
+    }
+}
+
+pub fn read_requirements(path: impl AsRef<Path>) -> Result<Vec<Dependency>, Error> {
+    requirements(&std::fs::read_to_string(path)?)
+}
+
+/// One requirement per line. Comments, blank lines and environment markers
+/// are skipped; options such as `-r` or `--index-url` are rejected rather
+/// than silently ignored.
+pub fn requirements(input: &str) -> Result<Vec<Dependency>, Error> {
+    let mut dependencies = Vec::new();
+    for (i, line) in input.lines().enumerate() {
+        let line = line.split_once('#').map_or(line, |(line, _)| line).trim();
+        if line.is_empty() {
+            continue;
+        }
+        let invalid = |message: String| Error::Invalid(format!("line {}: {message}", i + 1));
+        if line.starts_with('-') {
+            return Err(invalid(format!("`{line}` is not supported")));
+        }
+        dependencies.push(requirement(line).map_err(invalid)?);
+    }
+    Ok(dependencies)
+}
+
+pub fn read_index(path: impl AsRef<Path>) -> Result<Index, Error> {
+    index(&std::fs::read_to_string(path)?)
+}
+
+/// Reads an index snapshot in the format described at the top of this
+/// module.
+pub fn index(input: &str) -> Result<Index, Error> {
+    let document = toml::from_str::<toml::Table>(input)?;
+    let mut index = Index::new();
+    let Some(releases) = document.get("release") else {
+        return Ok(index);
+    };
+    let releases = releases
+        .as_array()
+        .ok_or_else(|| Error::Invalid("`release` is not an array of tables".into()))?;
+    for release in releases {
+        let table = release
+            .as_table()
+            .ok_or_else(|| Error::Invalid("`release` entry is not a table".into()))?;
+        let string = |key: &str| table.get(key).and_then(toml::Value::as_str);
+        let name = string("name").ok_or_else(|| Error::Invalid("release without a name".into()))?;
+        let invalid = |message: String| Error::Invalid(format!("release `{name}`: {message}"));
+        let version = string("version")
+            .ok_or_else(|| invalid("no version".into()))
+            .and_then(|v| version(v).map_err(invalid))?;
+        let dependencies = match table.get("requires-dist") {
+            None => Vec::new(),
+            Some(requires) => requires
+                .as_array()
+                .and_then(|r| {
+                    r.iter()
+                        .map(toml::Value::as_str)
+                        .collect::<Option<Vec<_>>>()
+                })
+                .ok_or_else(|| invalid("`requires-dist` is not an array of strings".into()))?
+                .into_iter()
+                .map(|r| requirement(r).map_err(invalid))
+                .collect::<Result<_, _>>()?,
+        };
+        index.add(&normalize(name), version, dependencies);
+    }
+    Ok(index)
+}
+
+/// The source of every package but the project's own. The index snapshot
+/// stands in for PyPI.
+pub const SOURCE: &str = "registry+https://pypi.org/simple";
+
+/// The packages pip would install for `resolution`. The project itself is
+/// the package named `root`, without a source, as in `npm::parse`.
+pub fn lockfile(resolution: &Resolution) -> Lockfile {
+    let reference = |&id: &usize| {
+        let resolved = &resolution.packages[id];
+        format!("{} {}", resolved.name, resolved.version)
+    };
+    let root = Package {
+        name: "root".to_string(),
+        version: Version::new(0, 0, 0),
+        source: None,
+        checksum: None,
+        dependencies: resolution.root.iter().map(reference).collect(),
+    };
+    let packages = resolution.packages.iter().map(|resolved| Package {
+        name: resolved.name.clone(),
+        version: resolved.version.clone(),
+        source: Some(SOURCE.to_string()),
+        checksum: None,
+        dependencies: resolved.dependencies.iter().map(reference).collect(),
+    });
+    Lockfile {
+        version: None,
+        packages: std::iter::once(root).chain(packages).collect(),
+    }
+}
+
+/// A PEP 508 requirement such as `Werkzeug>=2.0` or `click[extra]~=7.1`,
+/// under its normalized name.
+pub fn requirement(s: &str) -> Result<Dependency, String> {
+    // Environment markers decide whether the requirement applies at all;
+    // this model assumes they do.
+    let s = s.split_once(';').map_or(s, |(s, _)| s).trim();
+    let end = s
+        .find(|c: char| !(c.is_ascii_alphanumeric() || "-_.".contains(c)))
+        .unwrap_or(s.len());
+    let (name, rest) = s.split_at(end);
+    if name.is_empty() {
+        return Err(format!("`{s}` has no package name"));
+    }
+    let rest = rest.trim_start();
+    let rest = match rest.strip_prefix('[') {
+        Some(extras) => {
+            extras
+                .split_once(']')
+                .ok_or_else(|| format!("`{s}` has unclosed extras"))?
+                .1
+        }
+        None => rest,
+    };
+    Ok(Dependency::new(normalize(name), specifier(rest)?))
+}
+
+/// A PEP 440 version specifier as a SemVer requirement. Versions with fewer
+/// than three parts are padded with zeros, since `==2.0` means 2.0.0 to pip.
+pub fn specifier(s: &str) -> Result<VersionReq, String> {
+    let s = s.trim();
+    if s.is_empty() {
+        return Ok("*".parse().expect("`*` is a valid requirement"));
+    }
+    let mut comparators = Vec::new();
+    for clause in s.split(',').map(str::trim) {
+        let (op, version) = ["===", "~=", "==", "!=", ">=", "<=", ">", "<"]
+            .into_iter()
+            .find_map(|op| clause.strip_prefix(op).map(|v| (op, v.trim())))
+            .ok_or_else(|| format!("`{clause}` has no comparison operator"))?;
+        let parts: Vec<&str> = version.split('.').collect();
+        let translated = match op {
+            "===" | "!=" => return Err(format!("`{op}` in `{clause}` is not supported")),
+            "==" if version.ends_with(".*") => format!("={version}"),
+            "~=" => {
+                // `~=1.4.5` is `>=1.4.5, ==1.4.*`; `~=2.2` is `>=2.2, ==2.*`.
+                if parts.len() < 2 {
+                    return Err(format!("`{clause}` needs at least two version parts"));
+                }
+                let prefix = parts[..parts.len() - 1].join(".");
+                format!(">={}, ={prefix}.*", pad(version))
+            }
+            "==" => format!("={}", pad(version)),
+            op => format!("{op}{}", pad(version)),
+        };
+        let req: VersionReq = translated
+            .parse()
+            .map_err(|_| format!("`{clause}` is not a version specifier this model understands"))?;
+        comparators.extend(req.comparators);
+    }
+    Ok(VersionReq { comparators })
+}
+
+fn version(s: &str) -> Result<Version, String> {
+    pad(s)
+        .parse()
+        .map_err(|_| format!("`{s}` is not a version this model understands"))
+}
+
+fn pad(version: &str) -> String {
+    let parts = version.split('.').count();
+    let mut padded = version.to_string();
+    for _ in parts..3 {
+        padded.push_str(".0");
+    }
+    padded
+}
+
+/// PEP 503: case-insensitive, with runs of `-`, `_` and `.` all the same.
+pub fn normalize(name: &str) -> String {
+    let mut out = String::new();
+    for c in name.chars() {
+        if "-_.".contains(c) {
+            if !out.ends_with('-') {
+                out.push('-');
+            }
+        } else {
+            out.push(c.to_ascii_lowercase());
+        }
+    }
+    out
+}
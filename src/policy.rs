@@ -0,0 +1,282 @@
+//! Checks a lockfile's duplicated packages against a `duplicates.toml`
+//! policy:
+//!
+//! ```toml
+//! # For crates not listed below: "allow", "warn" (the default) or "deny".
+//! default = "warn"
+//!
+//! # `log` may only be duplicated by way of `b`.
+//! [crates.log]
+//! duplicates = "allow"
+//! via = ["b"]
+//!
+//! [crates.serde]
+//! duplicates = "deny"
+//! ```
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::duplicates::{self, Duplicate};
+use crate::graph::Graph;
+use crate::lockfile::Package;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Toml(toml::de::Error),
+    Invalid(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "failed to read policy: {e}"),
+            Error::Toml(e) => write!(f, "failed to parse policy: {e}"),
+            Error::Invalid(message) => write!(f, "invalid policy: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Toml(e) => Some(e),
+            Error::Invalid(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<toml::de::Error> for Error {
+    fn from(e: toml::de::Error) -> Self {
+        Error::Toml(e)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Allow,
+    Warn,
+    Deny,
+}
+
+impl FromStr for Level {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        match s {
+            "allow" => Ok(Level::Allow),
+            "warn" => Ok(Level::Warn),
+            "deny" => Ok(Level::Deny),
+            _ => Err(Error::Invalid(format!(
+                "`{s}` is not one of \"allow\", \"warn\" or \"deny\""
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Level::Allow => "allow",
+            Level::Warn => "warn",
+            Level::Deny => "deny",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    pub level: Level,
+    /// With `allow`: the packages every extra copy has to be reached
+    /// through. Empty means any.
+    pub via: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Policy {
+    pub default: Level,
+    pub crates: BTreeMap<String, Rule>,
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Policy {
+            default: Level::Warn,
+            crates: BTreeMap::new(),
+        }
+    }
+}
+
+impl Policy {
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn parse(input: &str) -> Result<Self, Error> {
+        let document = toml::from_str::<toml::Table>(input)?;
+        let mut policy = Policy::default();
+        for (key, value) in &document {
+            match key.as_str() {
+                "default" => policy.default = level(value, "default")?,
+                "crates" => {
+                    let crates = value
+                        .as_table()
+                        .ok_or_else(|| Error::Invalid("`crates` is not a table".into()))?;
+                    for (name, value) in crates {
+                        policy.crates.insert(name.clone(), rule(name, value)?);
+                    }
+                }
+                _ => return Err(Error::Invalid(format!("unknown key `{key}`"))),
+            }
+        }
+        Ok(policy)
+    }
+
+    pub fn rule(&self, name: &str) -> Rule {
+        self.crates.get(name).cloned().unwrap_or(Rule {
+            level: self.default,
+            via: Vec::new(),
+        })
+    }
+}
+
+fn level(value: &toml::Value, key: &str) -> Result<Level, Error> {
+    value
+        .as_str()
+        .ok_or_else(|| Error::Invalid(format!("`{key}` is not a string")))?
+        .parse()
+}
+
+fn rule(name: &str, value: &toml::Value) -> Result<Rule, Error> {
+    let table = value
+        .as_table()
+        .ok_or_else(|| Error::Invalid(format!("`crates.{name}` is not a table")))?;
+    let mut rule = Rule {
+        level: Level::Allow,
+        via: Vec::new(),
+    };
+    let mut level_set = false;
+    for (key, value) in table {
+        match key.as_str() {
+            "duplicates" => {
+                rule.level = level(value, &format!("crates.{name}.duplicates"))?;
+                level_set = true;
+            }
+            "via" => {
+                rule.via = value
+                    .as_array()
+                    .and_then(|via| via.iter().map(|v| v.as_str().map(String::from)).collect())
+                    .ok_or_else(|| {
+                        Error::Invalid(format!("`crates.{name}.via` is not an array of strings"))
+                    })?;
+            }
+            _ => return Err(Error::Invalid(format!("unknown key `crates.{name}.{key}`"))),
+        }
+    }
+    if !level_set {
+        return Err(Error::Invalid(format!(
+            "`crates.{name}` has no `duplicates` level"
+        )));
+    }
+    if !rule.via.is_empty() && rule.level != Level::Allow {
+        return Err(Error::Invalid(format!(
+            "`crates.{name}.via` only applies to `duplicates = \"allow\"`"
+        )));
+    }
+    Ok(rule)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Verdict<'a> {
+    Allowed,
+    Warned,
+    Denied,
+    /// The crate may only be duplicated via certain packages, but more than
+    /// one of these copies is reached without going through any of them.
+    NotVia(Vec<&'a Package>),
+}
+
+impl Verdict<'_> {
+    pub fn is_violation(&self) -> bool {
+        matches!(self, Verdict::Denied | Verdict::NotVia(_))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding<'a> {
+    pub duplicate: Duplicate<'a>,
+    pub rule: Rule,
+    pub verdict: Verdict<'a>,
+}
+
+/// Applies the policy to every duplicated package in the graph's lockfile,
+/// sorted by name. An [intentional](Graph::is_intentional) edge counts as
+/// going through `via`.
+pub fn check<'a>(policy: &Policy, graph: &Graph<'a>) -> Vec<Finding<'a>> {
+    duplicates::find(graph.lockfile())
+        .into_iter()
+        .map(|duplicate| {
+            let rule = policy.rule(duplicate.name);
+            let verdict = match rule.level {
+                Level::Warn => Verdict::Warned,
+                Level::Deny => Verdict::Denied,
+                Level::Allow if rule.via.is_empty() => Verdict::Allowed,
+                Level::Allow => {
+                    let reachable = reachable_avoiding(graph, &rule.via);
+                    // One copy is the one everybody else uses; only the
+                    // extra ones have to come in through `via`.
+                    let direct: Vec<&Package> = duplicate
+                        .packages
+                        .iter()
+                        .copied()
+                        .filter(|&p| graph.id(p).is_some_and(|id| reachable[id]))
+                        .collect();
+                    if direct.len() > 1 {
+                        Verdict::NotVia(direct)
+                    } else {
+                        Verdict::Allowed
+                    }
+                }
+            };
+            Finding {
+                duplicate,
+                rule,
+                verdict,
+            }
+        })
+        .collect()
+}
+
+// Marks the packages reachable from the roots without passing through a
+// package named in `via` or taking an intentional edge.
+fn reachable_avoiding(graph: &Graph<'_>, via: &[String]) -> Vec<bool> {
+    let avoided = |id: usize| via.contains(&graph.package(id).name);
+    let mut reachable = vec![false; graph.len()];
+    let mut queue: VecDeque<usize> = graph.roots().into_iter().collect();
+    for &root in &queue {
+        reachable[root] = true;
+    }
+    while let Some(id) = queue.pop_front() {
+        if avoided(id) {
+            continue;
+        }
+        for &dep in graph.dependencies(id) {
+            if !reachable[dep] && !graph.is_intentional(id, dep) {
+                reachable[dep] = true;
+                queue.push_back(dep);
+            }
+        }
+    }
+    reachable
+}
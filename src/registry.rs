@@ -0,0 +1,396 @@
+//! A local registry of fixture crates, so that consumers resolve against
+//! published versions offline just as they would against crates.io. Caret
+//! matching, picking the highest compatible version and yanking all apply
+//! here. Path dependencies bypass all three.
+//!
+//! Only the source of each release is checked in, under
+//! `fixtures/registry/<name>-<version>`. [`publish`] packs it into
+//! `<name>-<version>.crate` and writes its line of the index, in the layout
+//! Cargo reads from a `local-registry` source. A release is yanked by setting
+//! `package.metadata.registry.yanked = true` in its manifest.
+//!
+//! Consumers are pointed at the registry by replacing crates.io with it for
+//! a single Cargo invocation, so nothing else sees the fixtures.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Output;
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use sha2::{Digest, Sha256};
+
+use crate::cargo;
+use crate::lockfile::{self, Lockfile};
+use crate::manifest;
+use crate::semver::Version;
+use serde_json::{Map, Value};
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Manifest(manifest::Error),
+    Lockfile(lockfile::Error),
+    Cargo(cargo::Error),
+    Invalid(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "{e}"),
+            Error::Manifest(e) => write!(f, "{e}"),
+            Error::Lockfile(e) => write!(f, "{e}"),
+            Error::Cargo(e) => write!(f, "{e}"),
+            Error::Invalid(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Manifest(e) => Some(e),
+            Error::Lockfile(e) => Some(e),
+            Error::Cargo(e) => Some(e),
+            Error::Invalid(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<manifest::Error> for Error {
+    fn from(e: manifest::Error) -> Self {
+        Error::Manifest(e)
+    }
+}
+
+impl From<lockfile::Error> for Error {
+    fn from(e: lockfile::Error) -> Self {
+        Error::Lockfile(e)
+    }
+}
+
+impl From<cargo::Error> for Error {
+    fn from(e: cargo::Error) -> Self {
+        Error::Cargo(e)
+    }
+}
+
+/// The sources of the fixture releases, relative to the repository root.
+pub const DIR: &str = "fixtures/registry";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Release {
+    pub name: String,
+    pub version: Version,
+    pub yanked: bool,
+    /// The SHA-256 of the `.crate` file, as hex.
+    pub checksum: String,
+}
+
+/// Publishes every release under `crates` to `registry`. The index files
+/// are rewritten, so a release whose source is removed disappears from the
+/// index. Returns the releases, sorted by name and then version.
+pub fn publish(crates: &Path, registry: &Path) -> Result<Vec<Release>, Error> {
+    let mut dirs: Vec<PathBuf> = fs::read_dir(crates)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<io::Result<_>>()?;
+    dirs.sort();
+
+    let mut entries: Vec<(Release, Value)> = Vec::new();
+    for dir in dirs.iter().filter(|d| d.is_dir()) {
+        let path = dir.join("Cargo.toml");
+        let input = fs::read_to_string(&path)?;
+        let manifest = manifest::parse(&path, &input)?;
+        let document = toml::from_str::<toml::Table>(&input)
+            .map_err(|e| Error::Invalid(format!("{}: {e}", path.display())))?;
+        let name = manifest
+            .name
+            .clone()
+            .ok_or_else(|| Error::Invalid(format!("{} has no package name", path.display())))?;
+
+        let data = pack(dir, &name, &manifest.version)?;
+        fs::create_dir_all(registry)?;
+        fs::write(
+            registry.join(format!("{name}-{}.crate", manifest.version)),
+            &data,
+        )?;
+        let checksum: String = Sha256::digest(&data)
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect();
+        let yanked = lookup(&document, &["package", "metadata", "registry", "yanked"])
+            .and_then(toml::Value::as_bool)
+            .unwrap_or(false);
+        let entry = index_entry(&manifest, &document, &checksum, yanked)
+            .map_err(|message| Error::Invalid(format!("{}: {message}", path.display())))?;
+        entries.push((
+            Release {
+                name,
+                version: manifest.version,
+                yanked,
+                checksum,
+            },
+            entry,
+        ));
+    }
+    entries.sort_by(|(a, _), (b, _)| (&a.name, &a.version).cmp(&(&b.name, &b.version)));
+
+    let mut names: Vec<&str> = entries.iter().map(|(r, _)| r.name.as_str()).collect();
+    names.dedup();
+    for name in names {
+        let path = registry.join("index").join(index_path(name));
+        fs::create_dir_all(path.parent().expect("index paths have a directory"))?;
+        let lines: String = entries
+            .iter()
+            .filter(|(release, _)| release.name == name)
+            .map(|(_, entry)| format!("{}\n", entry))
+            .collect();
+        fs::write(path, lines)?;
+    }
+    Ok(entries.into_iter().map(|(release, _)| release).collect())
+}
+
+fn lookup<'a>(table: &'a toml::Table, keys: &[&str]) -> Option<&'a toml::Value> {
+    let (last, path) = keys.split_last()?;
+    let mut table = table;
+    for key in path {
+        table = table.get(*key)?.as_table()?;
+    }
+    table.get(*last)
+}
+
+// One line of the index, in the format described in the Cargo book's
+// "Registry Index" chapter.
+fn index_entry(
+    manifest: &manifest::Manifest,
+    document: &toml::Table,
+    checksum: &str,
+    yanked: bool,
+) -> Result<Value, String> {
+    let strings = |value: &toml::Value| -> Option<Vec<Value>> {
+        value
+            .as_array()?
+            .iter()
+            .map(|s| s.as_str().map(|s| Value::String(s.into())))
+            .collect()
+    };
+
+    let mut deps = Vec::new();
+    for dep in &manifest.dependencies {
+        if dep.path.is_some() {
+            return Err(format!("`{}` is a path dependency", dep.key));
+        }
+        let req = dep
+            .req
+            .as_deref()
+            .ok_or_else(|| format!("`{}` has no version requirement", dep.key))?;
+        let (target, section) = match dep
+            .section
+            .strip_prefix("target.'")
+            .and_then(|s| s.rsplit_once("'."))
+        {
+            Some((cfg, section)) => (Value::String(cfg.into()), section),
+            None => (Value::Null, dep.section.as_str()),
+        };
+        let kind = match section {
+            "dependencies" => "normal",
+            "build-dependencies" => "build",
+            _ => "dev",
+        };
+        let field = |key: &str| dep.value.as_table().and_then(|t| t.get(key));
+        let mut entry = Map::from_iter([
+            ("name".to_string(), Value::String(dep.key.clone())),
+            ("req".to_string(), Value::String(req.into())),
+            (
+                "features".to_string(),
+                Value::Array(field("features").and_then(strings).unwrap_or_default()),
+            ),
+            (
+                "optional".to_string(),
+                Value::Bool(field("optional").and_then(toml::Value::as_bool) == Some(true)),
+            ),
+            (
+                "default_features".to_string(),
+                Value::Bool(
+                    field("default-features").and_then(toml::Value::as_bool) != Some(false),
+                ),
+            ),
+            ("target".to_string(), target),
+            ("kind".to_string(), Value::String(kind.into())),
+        ]);
+        if dep.package != dep.key {
+            entry.insert("package".into(), Value::String(dep.package.clone()));
+        }
+        deps.push(Value::Object(entry));
+    }
+
+    let mut features = Map::new();
+    if let Some(table) = document.get("features").and_then(toml::Value::as_table) {
+        for (feature, enables) in table {
+            let enables = strings(enables)
+                .ok_or_else(|| format!("feature `{feature}` is not an array of strings"))?;
+            features.insert(feature.clone(), Value::Array(enables));
+        }
+    }
+    let links = lookup(document, &["package", "links"])
+        .and_then(toml::Value::as_str)
+        .map_or(Value::Null, |l| Value::String(l.into()));
+
+    Ok(Value::Object(Map::from_iter([
+        (
+            "name".to_string(),
+            Value::String(manifest.name.clone().unwrap_or_default()),
+        ),
+        (
+            "vers".to_string(),
+            Value::String(manifest.version.to_string()),
+        ),
+        ("deps".to_string(), Value::Array(deps)),
+        ("cksum".to_string(), Value::String(checksum.into())),
+        ("features".to_string(), Value::Object(features)),
+        ("yanked".to_string(), Value::Bool(yanked)),
+        ("links".to_string(), links),
+    ])))
+}
+
+/// Where a crate's index file lives: `1/a`, `2/ab`, `3/a/abc` or
+/// `ab/cd/abcd…`.
+pub fn index_path(name: &str) -> PathBuf {
+    let name = name.to_lowercase();
+    match name.len() {
+        1 => Path::new("1").join(&name),
+        2 => Path::new("2").join(&name),
+        3 => Path::new("3").join(&name[..1]).join(&name),
+        _ => Path::new(&name[..2]).join(&name[2..4]).join(&name),
+    }
+}
+
+/// The `.crate` file for the package in `dir`: a gzipped tarball with every
+/// file under `<name>-<version>/`. Files are added in sorted order with
+/// zeroed timestamps, so packing the same source twice gives the same bytes.
+pub fn pack(dir: &Path, name: &str, version: &Version) -> io::Result<Vec<u8>> {
+    let mut files = Vec::new();
+    walk(dir, &mut Vec::new(), &mut files)?;
+    let mut tar = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+    for file in files {
+        let data = fs::read(dir.join(&file))?;
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(0);
+        tar.append_data(&mut header, format!("{name}-{version}/{file}"), &data[..])?;
+    }
+    tar.into_inner()?.finish()
+}
+
+// Relative paths of the files under `dir`, `/`-separated, skipping build
+// output.
+fn walk(dir: &Path, prefix: &mut Vec<String>, files: &mut Vec<String>) -> io::Result<()> {
+    let mut entries: Vec<fs::DirEntry> = fs::read_dir(dir)?.collect::<io::Result<_>>()?;
+    entries.sort_by_key(fs::DirEntry::file_name);
+    for entry in entries {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name == "target" || name == "Cargo.lock" {
+            continue;
+        }
+        prefix.push(name);
+        if entry.file_type()?.is_dir() {
+            walk(&entry.path(), prefix, files)?;
+        } else {
+            files.push(prefix.join("/"));
+        }
+        prefix.pop();
+    }
+    Ok(())
+}
+
+/// Writes a workspace to `dir` whose root depends on one path crate per
+/// `(name, requirement)`. Each of those crates depends on `requirement` of
+/// `log`, as `a` and `b` do, and reports the version it got. A lockfile left
+/// by an earlier run is removed, since its checksums may be out of date.
+pub fn write_consumers(dir: &Path, consumers: &[(&str, &str)]) -> io::Result<()> {
+    match fs::remove_file(dir.join("Cargo.lock")) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+        _ => {}
+    }
+    let mut root_dependencies = String::new();
+    let mut main_rs = String::from("fn main() {\n");
+    for (name, requirement) in consumers {
+        let crate_dir = dir.join(name);
+        fs::create_dir_all(crate_dir.join("src"))?;
+        fs::write(
+            crate_dir.join("Cargo.toml"),
+            format!(
+                "[package]\nname = \"{name}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n\
+                 [dependencies]\nlog = \"{requirement}\"\n"
+            ),
+        )?;
+        fs::write(
+            crate_dir.join("src/lib.rs"),
+            "pub fn log_version() -> &'static str {\n    log::VERSION\n}\n",
+        )?;
+        root_dependencies.push_str(&format!("{name} = {{ path = \"{name}\" }}\n"));
+        main_rs.push_str(&format!(
+            "    println!(\"{name} {{}}\", {name}::log_version());\n"
+        ));
+    }
+    main_rs.push_str("}\n");
+
+    fs::create_dir_all(dir.join("src"))?;
+    fs::write(dir.join("src/main.rs"), main_rs)?;
+    fs::write(
+        dir.join("Cargo.toml"),
+        format!(
+            "[package]\nname = \"consumers\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n\
+             [dependencies]\n{root_dependencies}\n[workspace]\n"
+        ),
+    )
+}
+
+/// Resolves the workspace in `dir` against `registry` from scratch, as
+/// `cargo generate-lockfile` does, and returns the new lockfile.
+pub fn resolve(registry: &Path, dir: &Path) -> Result<Lockfile, Error> {
+    cargo(registry, dir, &["generate-lockfile"])?;
+    Ok(Lockfile::read(dir.join("Cargo.lock"))?)
+}
+
+/// Builds and runs the consumers in `dir`, returning the `log` version each
+/// one was compiled against.
+pub fn run(registry: &Path, dir: &Path) -> Result<Vec<(String, Version)>, Error> {
+    let output = cargo(registry, dir, &["run", "--quiet"])?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| {
+            let invalid = || Error::Invalid(format!("unexpected consumer output `{line}`"));
+            let (name, version) = line.split_once(' ').ok_or_else(invalid)?;
+            Ok((name.to_string(), version.parse().map_err(|_| invalid())?))
+        })
+        .collect()
+}
+
+// Runs Cargo on `dir` with crates.io replaced by `registry`.
+fn cargo(registry: &Path, dir: &Path, args: &[&str]) -> Result<Output, Error> {
+    let registry = format!(
+        "source.fixtures.local-registry = \"{}\"",
+        fs::canonicalize(registry)?.display()
+    );
+    let mut args = args.to_vec();
+    args.extend([
+        "--offline",
+        "--config",
+        "source.crates-io.replace-with = \"fixtures\"",
+        "--config",
+        &registry,
+    ]);
+    Ok(cargo::run(dir, &args)?)
+}
@@ -0,0 +1,474 @@
+//! A small dependency resolver over an in-memory package index, with the
+//! three answers package managers give when two dependents need incompatible
+//! versions of the same package: Cargo keeps one copy per SemVer-compatible
+//! range, pip keeps one copy per name and gives up, and npm nests another
+//! copy under whichever dependent can't use the one above it.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+
+use crate::graph::Graph;
+use crate::manifest::Manifest;
+use crate::semver::{self, Version, VersionReq};
+
+/// Every release of every package the resolver may pick from.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Index {
+    packages: BTreeMap<String, Vec<Release>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Release {
+    pub name: String,
+    pub version: Version,
+    pub dependencies: Vec<Dependency>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dependency {
+    pub name: String,
+    pub req: VersionReq,
+}
+
+impl Dependency {
+    pub fn new(name: impl Into<String>, req: VersionReq) -> Self {
+        Dependency {
+            name: name.into(),
+            req,
+        }
+    }
+}
+
+impl fmt::Display for Dependency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.name, self.req)
+    }
+}
+
+impl Index {
+    pub fn new() -> Self {
+        Index::default()
+    }
+
+    /// Adds a release, replacing any earlier one with the same version.
+    pub fn add(&mut self, name: &str, version: Version, dependencies: Vec<Dependency>) {
+        let releases = self.packages.entry(name.to_string()).or_default();
+        releases.retain(|r| r.version != version);
+        releases.push(Release {
+            name: name.to_string(),
+            version,
+            dependencies,
+        });
+        releases.sort_by(|a, b| a.version.cmp(&b.version));
+    }
+
+    /// The packages of a lockfile, one release each, minus dev-dependencies.
+    /// Local packages require what their manifests say. The lockfile doesn't
+    /// record what the others asked for, so they get a caret requirement on
+    /// whatever was locked. [Intentional](Graph::is_intentional) edges are
+    /// left out.
+    pub fn locked(graph: &Graph<'_>, manifests: &[Manifest]) -> Result<Self, semver::Error> {
+        let mut index = Index::new();
+        for id in 0..graph.len() {
+            let package = graph.package(id);
+            let locked: Vec<_> = graph
+                .dependencies(id)
+                .iter()
+                .filter(|&&dep| !graph.is_intentional(id, dep))
+                .map(|&dep| graph.package(dep))
+                .collect();
+            let manifest = manifests.iter().find(|m| {
+                package.source.is_none()
+                    && m.name.as_deref() == Some(&package.name)
+                    && m.version == package.version
+            });
+            let dependencies = match manifest {
+                Some(manifest) => manifest
+                    .dependencies
+                    .iter()
+                    .filter(|d| !d.section.ends_with("dev-dependencies"))
+                    .filter(|d| locked.iter().any(|p| p.name == d.package))
+                    .map(|d| {
+                        let req = d.req.as_deref().unwrap_or("*").parse()?;
+                        Ok(Dependency::new(&d.package, req))
+                    })
+                    .collect::<Result<_, semver::Error>>()?,
+                None => locked
+                    .iter()
+                    .map(|p| Ok(Dependency::new(&p.name, p.version.to_string().parse()?)))
+                    .collect::<Result<_, semver::Error>>()?,
+            };
+            index.add(&package.name, package.version.clone(), dependencies);
+        }
+        Ok(index)
+    }
+
+    /// Lowest version first.
+    pub fn releases(&self, name: &str) -> &[Release] {
+        self.packages.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    // Newest first, the order every strategy tries them in.
+    fn candidates<'a>(&'a self, dependency: &'a Dependency) -> impl Iterator<Item = &'a Release> {
+        self.releases(&dependency.name)
+            .iter()
+            .rev()
+            .filter(|r| dependency.req.matches(&r.version))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Resolution {
+    /// In the order they were picked.
+    pub packages: Vec<Resolved>,
+    /// The packages the root's own requirements resolved to.
+    pub root: Vec<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Resolved {
+    pub name: String,
+    pub version: Version,
+    /// The packages whose `node_modules` it is nested in, outermost first.
+    /// Empty for packages at the top, which is all of them outside npm.
+    pub location: Vec<String>,
+    pub dependencies: Vec<usize>,
+}
+
+impl Resolution {
+    /// Every copy of `name`, lowest version first.
+    pub fn copies(&self, name: &str) -> Vec<&Resolved> {
+        let mut copies: Vec<&Resolved> = self.packages.iter().filter(|p| p.name == name).collect();
+        copies.sort_by(|a, b| a.version.cmp(&b.version));
+        copies
+    }
+}
+
+impl Resolved {
+    /// Where npm would install it, e.g. `node_modules/b/node_modules/log`.
+    pub fn path(&self) -> String {
+        self.location
+            .iter()
+            .chain([&self.name])
+            .map(|name| format!("node_modules/{name}"))
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+}
+
+/// Why resolution failed: a requirement that no release can meet, given the
+/// versions already chosen.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    /// Boxed to keep `Result`s small.
+    pub wanted: Box<Demand>,
+    /// The versions of the same package already picked, which rule out every
+    /// matching release. Empty if nothing in the index matches at all.
+    pub chosen: Vec<Chosen>,
+}
+
+/// A requirement and who made it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Demand {
+    /// The depending release's name and version, or `None` for the root.
+    pub by: Option<(String, Version)>,
+    pub dependency: Dependency,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chosen {
+    pub version: Version,
+    pub demanded_by: Vec<Demand>,
+}
+
+impl fmt::Display for Demand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.by {
+            Some((name, version)) => write!(f, "{name} {version} requires {}", self.dependency),
+            None => write!(f, "the root requires {}", self.dependency),
+        }
+    }
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.chosen.is_empty() {
+            return write!(f, "{}, but no release matches", self.wanted);
+        }
+        write!(f, "{}, but", self.wanted)?;
+        for (i, chosen) in self.chosen.iter().enumerate() {
+            let demands: Vec<String> = chosen.demanded_by.iter().map(Demand::to_string).collect();
+            let separator = if i > 0 { " and" } else { "" };
+            write!(
+                f,
+                "{separator} {} {} was already chosen because {}",
+                self.wanted.dependency.name,
+                chosen.version,
+                demands.join(" and ")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Conflict {}
+
+pub trait Strategy {
+    fn name(&self) -> &'static str;
+
+    /// Picks a release for every requirement reachable from `root`.
+    fn resolve(&self, index: &Index, root: &[Dependency]) -> Result<Resolution, Conflict>;
+}
+
+/// One copy per SemVer-compatible range: `log` 0.3.9 and 0.4.22 can both be
+/// picked, 0.4.0 and 0.4.22 can't.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Cargo;
+
+/// One copy per name.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Pip;
+
+/// Each dependency is looked up in the dependent's `node_modules`, then each
+/// enclosing one. If the first copy found doesn't match, a new one is nested
+/// in the dependent's own `node_modules`; if there is none, one is added at
+/// the top.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Npm;
+
+impl Strategy for Cargo {
+    fn name(&self) -> &'static str {
+        "cargo"
+    }
+
+    fn resolve(&self, index: &Index, root: &[Dependency]) -> Result<Resolution, Conflict> {
+        flat(index, root, &|release| {
+            semver::compatible_requirement(&release.version)
+        })
+    }
+}
+
+impl Strategy for Pip {
+    fn name(&self) -> &'static str {
+        "pip"
+    }
+
+    fn resolve(&self, index: &Index, root: &[Dependency]) -> Result<Resolution, Conflict> {
+        flat(index, root, &|_| String::new())
+    }
+}
+
+// A release picked by a flat strategy, and the requirements it satisfies.
+#[derive(Debug, Clone)]
+struct Activation<'a> {
+    release: &'a Release,
+    slot: String,
+    demands: Vec<(Option<usize>, &'a Dependency)>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct State<'a> {
+    activated: Vec<Activation<'a>>,
+    pending: VecDeque<(Option<usize>, &'a Dependency)>,
+    links: Vec<(Option<usize>, usize)>,
+}
+
+impl State<'_> {
+    fn demand(&self, from: Option<usize>, dependency: &Dependency) -> Demand {
+        Demand {
+            by: from.map(|id| {
+                let release = self.activated[id].release;
+                (release.name.clone(), release.version.clone())
+            }),
+            dependency: dependency.clone(),
+        }
+    }
+
+    fn conflict(&self, from: Option<usize>, dependency: &Dependency) -> Conflict {
+        Conflict {
+            wanted: Box::new(self.demand(from, dependency)),
+            chosen: self
+                .activated
+                .iter()
+                .filter(|a| a.release.name == dependency.name)
+                .map(|a| Chosen {
+                    version: a.release.version.clone(),
+                    demanded_by: a
+                        .demands
+                        .iter()
+                        .map(|&(from, dependency)| self.demand(from, dependency))
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+}
+
+// Every package gets one slot per distinct `slot` key, and a release can only
+// be picked if its slot is free or already holds that release. Requirements
+// are resolved breadth first, newest release first, backtracking on failure.
+fn flat(
+    index: &Index,
+    root: &[Dependency],
+    slot: &dyn Fn(&Release) -> String,
+) -> Result<Resolution, Conflict> {
+    let state = State {
+        pending: root.iter().map(|d| (None, d)).collect(),
+        ..State::default()
+    };
+    let mut conflict = None;
+    let Some(state) = search(index, state, slot, &mut conflict) else {
+        return Err(conflict.expect("a failed search records its conflict"));
+    };
+
+    let mut resolution = Resolution {
+        packages: state
+            .activated
+            .iter()
+            .map(|a| Resolved {
+                name: a.release.name.clone(),
+                version: a.release.version.clone(),
+                location: Vec::new(),
+                dependencies: Vec::new(),
+            })
+            .collect(),
+        root: Vec::new(),
+    };
+    for (from, to) in state.links {
+        match from {
+            Some(from) => resolution.packages[from].dependencies.push(to),
+            None => resolution.root.push(to),
+        }
+    }
+    Ok(resolution)
+}
+
+// Reports the first dead end it runs into, which is the one closest to the
+// root's own requirements.
+fn search<'a>(
+    index: &'a Index,
+    mut state: State<'a>,
+    slot: &dyn Fn(&Release) -> String,
+    conflict: &mut Option<Conflict>,
+) -> Option<State<'a>> {
+    let Some((from, dependency)) = state.pending.pop_front() else {
+        return Some(state);
+    };
+    for release in index.candidates(dependency) {
+        let key = slot(release);
+        let existing = state
+            .activated
+            .iter()
+            .position(|a| a.release.name == release.name && a.slot == key);
+        let mut next = state.clone();
+        let id = match existing {
+            Some(id) if state.activated[id].release.version == release.version => id,
+            Some(_) => continue,
+            None => {
+                next.activated.push(Activation {
+                    release,
+                    slot: key,
+                    demands: Vec::new(),
+                });
+                let id = next.activated.len() - 1;
+                next.pending
+                    .extend(release.dependencies.iter().map(|d| (Some(id), d)));
+                id
+            }
+        };
+        next.activated[id].demands.push((from, dependency));
+        next.links.push((from, id));
+        if let Some(done) = search(index, next, slot, conflict) {
+            return Some(done);
+        }
+    }
+    conflict.get_or_insert_with(|| state.conflict(from, dependency));
+    None
+}
+
+impl Strategy for Npm {
+    fn name(&self) -> &'static str {
+        "npm"
+    }
+
+    fn resolve(&self, index: &Index, root: &[Dependency]) -> Result<Resolution, Conflict> {
+        let mut resolution = Resolution::default();
+        // The directory each package is installed in, `None` being the top.
+        let mut parents: Vec<Option<usize>> = Vec::new();
+        let mut node_modules: BTreeMap<Option<usize>, BTreeMap<&str, usize>> = BTreeMap::new();
+        let mut queue: VecDeque<(Option<usize>, &Dependency)> =
+            root.iter().map(|d| (None, d)).collect();
+
+        while let Some((from, dependency)) = queue.pop_front() {
+            let demand = |resolution: &Resolution| Demand {
+                by: from.map(|id| {
+                    let package: &Resolved = &resolution.packages[id];
+                    (package.name.clone(), package.version.clone())
+                }),
+                dependency: dependency.clone(),
+            };
+
+            let mut dir = from;
+            let found = loop {
+                if let Some(&id) = node_modules
+                    .get(&dir)
+                    .and_then(|packages| packages.get(dependency.name.as_str()))
+                {
+                    break Some((dir, id));
+                }
+                match dir {
+                    Some(id) => dir = parents[id],
+                    None => break None,
+                }
+            };
+            let id = match found {
+                Some((_, id)) if dependency.req.matches(&resolution.packages[id].version) => id,
+                // Nowhere left to nest it.
+                Some((dir, id)) if dir == from => {
+                    return Err(Conflict {
+                        wanted: Box::new(demand(&resolution)),
+                        chosen: vec![Chosen {
+                            version: resolution.packages[id].version.clone(),
+                            demanded_by: Vec::new(),
+                        }],
+                    });
+                }
+                found => {
+                    let release = index
+                        .candidates(dependency)
+                        .next()
+                        .ok_or_else(|| Conflict {
+                            wanted: Box::new(demand(&resolution)),
+                            chosen: Vec::new(),
+                        })?;
+                    let dir = if found.is_some() { from } else { None };
+                    let mut location = Vec::new();
+                    let mut parent = dir;
+                    while let Some(id) = parent {
+                        location.insert(0, resolution.packages[id].name.clone());
+                        parent = parents[id];
+                    }
+                    let id = resolution.packages.len();
+                    resolution.packages.push(Resolved {
+                        name: release.name.clone(),
+                        version: release.version.clone(),
+                        location,
+                        dependencies: Vec::new(),
+                    });
+                    parents.push(dir);
+                    node_modules
+                        .entry(dir)
+                        .or_default()
+                        .insert(&release.name, id);
+                    queue.extend(release.dependencies.iter().map(|d| (Some(id), d)));
+                    id
+                }
+            };
+            match from {
+                Some(from) => resolution.packages[from].dependencies.push(id),
+                None => resolution.root.push(id),
+            }
+        }
+        Ok(resolution)
+    }
+}
@@ -0,0 +1,340 @@
+//! Software bills of materials for one package of a lockfile, as CycloneDX
+//! JSON or SPDX tag-value. Every resolved package is its own component, so
+//! duplicated crates show up once per version, each with its own checksum.
+
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::graph::Graph;
+use crate::lockfile::Package;
+use crate::manifest::Manifest;
+use serde_json::{Map as Object, Value};
+
+const CRATES_IO: [&str; 2] = [
+    "registry+https://github.com/rust-lang/crates.io-index",
+    "sparse+https://index.crates.io/",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sbom<'a> {
+    pub root: &'a Package,
+    /// The root first, then everything it depends on in lockfile order.
+    pub components: Vec<Component<'a>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Component<'a> {
+    pub package: &'a Package,
+    pub depends_on: Vec<&'a Package>,
+}
+
+impl<'a> Sbom<'a> {
+    /// Collects everything `root` needs at build and run time. The lockfile
+    /// doesn't say which edges are dev-dependencies, so the local packages'
+    /// `manifests` are used to leave those out.
+    pub fn new(graph: &Graph<'a>, root: usize, manifests: &[Manifest]) -> Self {
+        let edges = |id: usize| -> Vec<usize> {
+            let package = graph.package(id);
+            let manifest = manifests.iter().find(|m| {
+                package.source.is_none()
+                    && m.name.as_deref() == Some(&package.name)
+                    && m.version == package.version
+            });
+            graph
+                .dependencies(id)
+                .iter()
+                .copied()
+                .filter(|&dep| manifest.is_none_or(|m| !dev_only(m, &graph.package(dep).name)))
+                .collect()
+        };
+        let mut reached = BTreeSet::from([root]);
+        let mut stack = vec![root];
+        while let Some(id) = stack.pop() {
+            for dep in edges(id) {
+                if reached.insert(dep) {
+                    stack.push(dep);
+                }
+            }
+        }
+        let order = std::iter::once(root).chain(reached.iter().copied().filter(|&id| id != root));
+        let components = order
+            .map(|id| Component {
+                package: graph.package(id),
+                depends_on: edges(id)
+                    .into_iter()
+                    .map(|dep| graph.package(dep))
+                    .collect(),
+            })
+            .collect();
+        Sbom {
+            root: graph.package(root),
+            components,
+        }
+    }
+}
+
+// Whether the manifest only ever declares `name` as a dev-dependency.
+fn dev_only(manifest: &Manifest, name: &str) -> bool {
+    let mut sections = manifest
+        .dependencies
+        .iter()
+        .filter(|d| d.package == name)
+        .map(|d| d.section.ends_with("dev-dependencies"))
+        .peekable();
+    sections.peek().is_some() && sections.all(|dev| dev)
+}
+
+/// The package URL, for packages that came from a registry or git.
+pub fn purl(package: &Package) -> Option<String> {
+    let source = package.source.as_deref()?;
+    let base = format!("pkg:cargo/{}@{}", package.name, package.version);
+    Some(if CRATES_IO.contains(&source) {
+        base
+    } else if let Some(url) = source.strip_prefix("git+") {
+        format!("{base}?vcs_url={}", percent_encode(&format!("git+{url}")))
+    } else {
+        let url = source.split_once('+').map_or(source, |(_, url)| url);
+        format!("{base}?repository_url={}", percent_encode(url))
+    })
+}
+
+fn percent_encode(s: &str) -> String {
+    let mut out = String::new();
+    for byte in s.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            out.push(byte as char);
+        } else {
+            write!(out, "%{byte:02X}").expect("writing to a String cannot fail");
+        }
+    }
+    out
+}
+
+// Unique within the document: the purl where there is one, otherwise the
+// lockfile's `name version`.
+fn bom_ref(package: &Package) -> String {
+    purl(package).unwrap_or_else(|| format!("{} {}", package.name, package.version))
+}
+
+/// A CycloneDX 1.5 document.
+pub fn cyclonedx(sbom: &Sbom<'_>) -> Value {
+    let component = |package: &Package, kind: &str| {
+        let mut object = Object::new();
+        object.insert("type".into(), Value::String(kind.into()));
+        object.insert("bom-ref".into(), Value::String(bom_ref(package)));
+        object.insert("name".into(), Value::String(package.name.clone()));
+        object.insert("version".into(), Value::String(package.version.to_string()));
+        if let Some(purl) = purl(package) {
+            object.insert("purl".into(), Value::String(purl));
+        }
+        if let Some(checksum) = &package.checksum {
+            let mut hash = Object::new();
+            hash.insert("alg".into(), Value::String("SHA-256".into()));
+            hash.insert("content".into(), Value::String(checksum.clone()));
+            object.insert("hashes".into(), Value::Array(vec![Value::Object(hash)]));
+        }
+        Value::Object(object)
+    };
+
+    let mut metadata = Object::new();
+    metadata.insert("component".into(), component(sbom.root, "application"));
+    let components = sbom.components[1..]
+        .iter()
+        .map(|c| component(c.package, "library"))
+        .collect();
+    let dependencies = sbom
+        .components
+        .iter()
+        .map(|c| {
+            let mut object = Object::new();
+            object.insert("ref".into(), Value::String(bom_ref(c.package)));
+            object.insert(
+                "dependsOn".into(),
+                Value::Array(
+                    c.depends_on
+                        .iter()
+                        .map(|&p| Value::String(bom_ref(p)))
+                        .collect(),
+                ),
+            );
+            Value::Object(object)
+        })
+        .collect();
+
+    let mut document = Object::new();
+    document.insert("bomFormat".into(), Value::String("CycloneDX".into()));
+    document.insert("specVersion".into(), Value::String("1.5".into()));
+    document.insert("version".into(), Value::from(1));
+    document.insert("metadata".into(), Value::Object(metadata));
+    document.insert("components".into(), Value::Array(components));
+    document.insert("dependencies".into(), Value::Array(dependencies));
+    Value::Object(document)
+}
+
+/// An SPDX 2.3 tag-value document, stamped with `created`.
+pub fn spdx(sbom: &Sbom<'_>, created: SystemTime) -> String {
+    // SPDX identifiers only allow letters, digits, `.` and `-`.
+    let mut ids: Vec<String> = Vec::new();
+    for component in &sbom.components {
+        let package = component.package;
+        let mut id: String = format!("SPDXRef-{}-{}", package.name, package.version)
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                    c
+                } else {
+                    '-'
+                }
+            })
+            .collect();
+        if ids.contains(&id) {
+            id = format!("{id}-{}", ids.len());
+        }
+        ids.push(id);
+    }
+    let id = |package: &Package| {
+        let index = sbom
+            .components
+            .iter()
+            .position(|c| std::ptr::eq(c.package, package))
+            .expect("dependencies are components too");
+        &ids[index]
+    };
+
+    let name = format!("{}-{}", sbom.root.name, sbom.root.version);
+    let mut out = String::new();
+    line(&mut out, "SPDXVersion", "SPDX-2.3");
+    line(&mut out, "DataLicense", "CC0-1.0");
+    line(&mut out, "SPDXID", "SPDXRef-DOCUMENT");
+    line(&mut out, "DocumentName", &name);
+    line(
+        &mut out,
+        "DocumentNamespace",
+        &format!(
+            "https://spdx.org/spdxdocs/{name}-{:016x}",
+            fingerprint(sbom)
+        ),
+    );
+    line(
+        &mut out,
+        "Creator",
+        &format!(
+            "Tool: {}-{}",
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION")
+        ),
+    );
+    line(&mut out, "Created", &timestamp(created));
+    line(
+        &mut out,
+        "Relationship",
+        &format!("SPDXRef-DOCUMENT DESCRIBES {}", id(sbom.root)),
+    );
+
+    for component in &sbom.components {
+        let package = component.package;
+        out.push('\n');
+        line(&mut out, "PackageName", &package.name);
+        line(&mut out, "SPDXID", id(package));
+        line(&mut out, "PackageVersion", &package.version.to_string());
+        line(
+            &mut out,
+            "PackageDownloadLocation",
+            &download_location(package),
+        );
+        line(&mut out, "FilesAnalyzed", "false");
+        if let Some(checksum) = &package.checksum {
+            line(&mut out, "PackageChecksum", &format!("SHA256: {checksum}"));
+        }
+        line(&mut out, "PackageLicenseConcluded", "NOASSERTION");
+        line(&mut out, "PackageLicenseDeclared", "NOASSERTION");
+        line(&mut out, "PackageCopyrightText", "NOASSERTION");
+        if let Some(purl) = purl(package) {
+            line(
+                &mut out,
+                "ExternalRef",
+                &format!("PACKAGE-MANAGER purl {purl}"),
+            );
+        }
+    }
+
+    out.push('\n');
+    for component in &sbom.components {
+        for &dependency in &component.depends_on {
+            line(
+                &mut out,
+                "Relationship",
+                &format!("{} DEPENDS_ON {}", id(component.package), id(dependency)),
+            );
+        }
+    }
+    out
+}
+
+fn line(out: &mut String, tag: &str, value: &str) {
+    writeln!(out, "{tag}: {value}").expect("writing to a String cannot fail");
+}
+
+fn download_location(package: &Package) -> String {
+    match package.source.as_deref() {
+        Some(source) if CRATES_IO.contains(&source) => format!(
+            "https://crates.io/api/v1/crates/{}/{}/download",
+            package.name, package.version
+        ),
+        // `git+https://host/repo?branch=main#commit` becomes
+        // `git+https://host/repo@commit`.
+        Some(source) if source.starts_with("git+") => {
+            let (url, commit) = source.split_once('#').unwrap_or((source, ""));
+            let url = url.split_once('?').map_or(url, |(url, _)| url);
+            if commit.is_empty() {
+                url.to_string()
+            } else {
+                format!("{url}@{commit}")
+            }
+        }
+        _ => "NOASSERTION".to_string(),
+    }
+}
+
+// FNV-1a over the components, so the namespace changes with the contents.
+fn fingerprint(sbom: &Sbom<'_>) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for component in &sbom.components {
+        let package = component.package;
+        let key = format!(
+            "{} {} {}\n",
+            package.name,
+            package.version,
+            package.source.as_deref().unwrap_or("")
+        );
+        for byte in key.bytes() {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    hash
+}
+
+/// `YYYY-MM-DDThh:mm:ssZ` in UTC.
+pub fn timestamp(time: SystemTime) -> String {
+    let seconds = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let (days, rest) = (seconds / 86_400, seconds % 86_400);
+    // Days since 1970-01-01 to a civil date, after Howard Hinnant's
+    // `civil_from_days`.
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        rest / 3600,
+        rest % 3600 / 60,
+        rest % 60
+    )
+}
@@ -0,0 +1,272 @@
+//! Generates throwaway workspaces shaped like the README's demo, but with
+//! any number of `a`/`b`-style leaves. Each leaf starts a chain of crates
+//! that ends in one copy of a shared crate. Every version of the shared
+//! crate is written out as its own path dependency. A diamond is two
+//! one-crate chains, a deep chain is one long one, and "five versions of one
+//! crate" is five chains ending in five different copies.
+//!
+//! Building a scenario runs the binary, and each leaf reports which version
+//! of the shared crate it reached and which copy: the address of a static
+//! that every copy has its own instance of.
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::cargo;
+use crate::lockfile::{self, Lockfile};
+use crate::semver::Version;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Lockfile(lockfile::Error),
+    Cargo(cargo::Error),
+    Invalid(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "{e}"),
+            Error::Lockfile(e) => write!(f, "{e}"),
+            Error::Cargo(e) => write!(f, "{e}"),
+            Error::Invalid(message) => write!(f, "unexpected scenario output: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Lockfile(e) => Some(e),
+            Error::Cargo(e) => Some(e),
+            Error::Invalid(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<lockfile::Error> for Error {
+    fn from(e: lockfile::Error) -> Self {
+        Error::Lockfile(e)
+    }
+}
+
+impl From<cargo::Error> for Error {
+    fn from(e: cargo::Error) -> Self {
+        Error::Cargo(e)
+    }
+}
+
+/// The name of the crate every chain ends in.
+pub const SHARED: &str = "shared";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scenario {
+    pub chains: Vec<Chain>,
+}
+
+/// `leaf{i}`, which the root depends on, then `leaf{i}-1` up to
+/// `leaf{i}-{depth - 1}`, each depending on the next. The last one depends
+/// on `version` of the shared crate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chain {
+    pub depth: usize,
+    pub version: Version,
+}
+
+impl Scenario {
+    /// One leaf per version, each depending on the shared crate directly.
+    pub fn fan(versions: &[Version]) -> Self {
+        Scenario::deep(1, versions)
+    }
+
+    /// One chain of `depth` crates per version.
+    pub fn deep(depth: usize, versions: &[Version]) -> Self {
+        Scenario {
+            chains: versions
+                .iter()
+                .map(|version| Chain {
+                    depth,
+                    version: version.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    /// The distinct shared versions, lowest first.
+    pub fn versions(&self) -> Vec<&Version> {
+        let versions: BTreeSet<&Version> = self.chains.iter().map(|c| &c.version).collect();
+        versions.into_iter().collect()
+    }
+
+    /// Writes the workspace to `dir`: the root package `scenario`, the
+    /// chains, and each shared version under `shared/<version>`. The shared
+    /// copies are excluded from the workspace, which may not contain two
+    /// packages with the same name.
+    pub fn write(&self, dir: &Path) -> io::Result<()> {
+        for version in self.versions() {
+            let crate_dir = dir.join(SHARED).join(version.to_string());
+            fs::create_dir_all(crate_dir.join("src"))?;
+            fs::write(
+                crate_dir.join("Cargo.toml"),
+                manifest(SHARED, &version.to_string(), ""),
+            )?;
+            fs::write(crate_dir.join("src/lib.rs"), SHARED_LIB_RS)?;
+        }
+
+        let mut root_dependencies = String::new();
+        let mut main_rs = String::from("fn main() {\n");
+        for (i, chain) in self.chains.iter().enumerate() {
+            let names: Vec<String> = (0..chain.depth.max(1))
+                .map(|level| match level {
+                    0 => format!("leaf{i}"),
+                    level => format!("leaf{i}-{level}"),
+                })
+                .collect();
+            for (level, name) in names.iter().enumerate() {
+                let dependency = match names.get(level + 1) {
+                    Some(next) => {
+                        format!("next = {{ package = \"{next}\", path = \"../{next}\" }}\n")
+                    }
+                    None => format!(
+                        "next = {{ package = \"{SHARED}\", path = \"../{SHARED}/{}\" }}\n",
+                        chain.version
+                    ),
+                };
+                let crate_dir = dir.join(name);
+                fs::create_dir_all(crate_dir.join("src"))?;
+                fs::write(
+                    crate_dir.join("Cargo.toml"),
+                    manifest(name, "0.1.0", &dependency),
+                )?;
+                fs::write(crate_dir.join("src/lib.rs"), LEAF_LIB_RS)?;
+            }
+            root_dependencies.push_str(&format!("leaf{i} = {{ path = \"leaf{i}\" }}\n"));
+            main_rs.push_str(&format!(
+                "    let (version, copy) = leaf{i}::report();\n    \
+                 println!(\"leaf{i} {{version}} {{copy:x}}\");\n"
+            ));
+        }
+        main_rs.push_str("}\n");
+
+        fs::create_dir_all(dir.join("src"))?;
+        fs::write(dir.join("src/main.rs"), main_rs)?;
+        fs::write(
+            dir.join("Cargo.toml"),
+            format!(
+                "{}\n[workspace]\nexclude = [\"{SHARED}\"]\n",
+                manifest("scenario", "0.1.0", &root_dependencies)
+            ),
+        )
+    }
+}
+
+const SHARED_LIB_RS: &str = "\
+use std::sync::atomic::AtomicU8;
+
+// Every copy of this crate in the binary has its own.
+static COPY: AtomicU8 = AtomicU8::new(0);
+
+pub fn report() -> (&'static str, usize) {
+    (env!(\"CARGO_PKG_VERSION\"), &COPY as *const AtomicU8 as usize)
+}
+";
+
+const LEAF_LIB_RS: &str = "\
+pub fn report() -> (&'static str, usize) {
+    next::report()
+}
+";
+
+fn manifest(name: &str, version: &str, dependencies: &str) -> String {
+    let mut manifest = format!(
+        "[package]\n\
+         name = \"{name}\"\n\
+         version = \"{version}\"\n\
+         edition = \"2021\"\n"
+    );
+    if !dependencies.is_empty() {
+        manifest.push_str("\n[dependencies]\n");
+        manifest.push_str(dependencies);
+    }
+    manifest
+}
+
+/// What a built scenario reported.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Outcome {
+    /// The shared crate's versions in the generated `Cargo.lock`, lowest
+    /// first, once per locked package.
+    pub locked: Vec<Version>,
+    /// One per chain, in order.
+    pub reports: Vec<Report>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub leaf: String,
+    pub version: Version,
+    /// Which copy of the shared crate the leaf reached, numbered from 1 in
+    /// the order the copies were first seen.
+    pub copy: usize,
+}
+
+impl Outcome {
+    /// The number of distinct copies of the shared crate seen at run time.
+    pub fn copies(&self) -> usize {
+        self.reports.iter().map(|r| r.copy).max().unwrap_or(0)
+    }
+}
+
+/// Builds the scenario written to `dir` and runs it. Everything is a path
+/// dependency, so this works offline.
+pub fn build(dir: &Path) -> Result<Outcome, Error> {
+    let output = cargo::run(dir, &["run", "--quiet", "--offline"])?;
+
+    let lockfile = Lockfile::read(dir.join("Cargo.lock"))?;
+    let mut locked: Vec<Version> = lockfile
+        .packages
+        .into_iter()
+        .filter(|p| p.name == SHARED)
+        .map(|p| p.version)
+        .collect();
+    locked.sort();
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut addresses: Vec<&str> = Vec::new();
+    let mut reports = Vec::new();
+    for line in stdout.lines() {
+        let mut words = line.split(' ');
+        let (Some(leaf), Some(version), Some(address), None) =
+            (words.next(), words.next(), words.next(), words.next())
+        else {
+            return Err(Error::Invalid(line.to_string()));
+        };
+        let version = version
+            .parse()
+            .map_err(|_| Error::Invalid(line.to_string()))?;
+        let copy = match addresses.iter().position(|&a| a == address) {
+            Some(i) => i + 1,
+            None => {
+                addresses.push(address);
+                addresses.len()
+            }
+        };
+        reports.push(Report {
+            leaf: leaf.to_string(),
+            version,
+            copy,
+        });
+    }
+    Ok(Outcome { locked, reports })
+}
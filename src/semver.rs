@@ -0,0 +1,25 @@
+//! SemVer versions as they appear in `Cargo.lock`, and the version
+//! requirements of `Cargo.toml`. Both come from the `semver` crate, which
+//! Cargo itself uses; this adds Cargo's notion of compatible versions.
+
+pub use ::semver::{Comparator, Error, Op, Version, VersionReq};
+
+/// Whether Cargo would let one of the two versions stand in for the other:
+/// they agree up to and including the leftmost non-zero part.
+pub fn is_compatible(a: &Version, b: &Version) -> bool {
+    match (a.major, a.minor) {
+        (0, 0) => b.major == 0 && b.minor == 0 && a.patch == b.patch,
+        (0, minor) => b.major == 0 && b.minor == minor,
+        (major, _) => b.major == major,
+    }
+}
+
+/// The shortest requirement that accepts `version` and everything
+/// compatible with it: `"0.4"` for 0.4.22, `"2"` for 2.0.0.
+pub fn compatible_requirement(version: &Version) -> String {
+    match (version.major, version.minor) {
+        (0, 0) => format!("0.0.{}", version.patch),
+        (0, minor) => format!("0.{minor}"),
+        (major, _) => major.to_string(),
+    }
+}
@@ -0,0 +1,300 @@
+//! Attributes the symbols of a linked executable to the crates that defined
+//! them, and ties each copy of a crate back to the version it was built from.
+//!
+//! Only v0 symbol names (`-C symbol-mangling-version=v0`, `_R...`) record
+//! every crate with its disambiguator, a hash that differs between two
+//! versions of the same crate. Legacy `_ZN...` names only carry the crate
+//! name, so two copies of `log` cannot be told apart from those. [`build`]
+//! turns v0 on for a single build in a target directory of its own.
+
+use std::collections::{BTreeMap, HashSet};
+use std::env;
+use std::ffi::OsStr;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use object::read::archive::ArchiveFile;
+use object::{Object, ObjectSymbol};
+
+use crate::cargo;
+use crate::depinfo;
+use crate::semver::Version;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Object(object::Error),
+    Cargo(cargo::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "{e}"),
+            Error::Object(e) => write!(f, "not a supported object file: {e}"),
+            Error::Cargo(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Object(e) => Some(e),
+            Error::Cargo(e) => Some(e),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<object::Error> for Error {
+    fn from(e: object::Error) -> Self {
+        Error::Object(e)
+    }
+}
+
+impl From<cargo::Error> for Error {
+    fn from(e: cargo::Error) -> Self {
+        Error::Cargo(e)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Crate {
+    pub name: String,
+    /// `None` for legacy-mangled symbols.
+    pub disambiguator: Option<u64>,
+}
+
+impl fmt::Display for Crate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.disambiguator {
+            Some(hash) => write!(f, "{}[{hash:016x}]", self.name),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Demangled {
+    /// The demangled path, e.g. `<log::Level as core::fmt::Display>::fmt`.
+    pub path: String,
+    /// The first crate named in the path: the one defining the item, or for
+    /// a trait impl the crate of the implementing type.
+    pub krate: Crate,
+}
+
+/// Demangles a v0 or legacy Rust symbol. Returns `None` for anything else,
+/// such as C symbols from libc, and for paths that name no crate, such as
+/// `<str>::len`.
+pub fn demangle(symbol: &str) -> Option<Demangled> {
+    let demangled = rustc_demangle::try_demangle(symbol).ok()?;
+    // The alternate form leaves out hashes. The plain one keeps v0's crate
+    // disambiguators, as in `log[560f7867cf10e885]::LOGGER`.
+    let path = format!("{demangled:#}");
+    let krate = if symbol.trim_start_matches('_').starts_with('R') {
+        v0_crate(&demangled.to_string())?
+    } else {
+        legacy_crate(&path)?
+    };
+    Some(Demangled { path, krate })
+}
+
+fn v0_crate(printed: &str) -> Option<Crate> {
+    printed.match_indices('[').find_map(|(open, _)| {
+        let close = open + printed[open..].find(']')?;
+        let disambiguator = u64::from_str_radix(&printed[open + 1..close], 16).ok()?;
+        let name = identifier_before(printed, open)?;
+        Some(Crate {
+            name: name.to_string(),
+            disambiguator: Some(disambiguator),
+        })
+    })
+}
+
+fn legacy_crate(path: &str) -> Option<Crate> {
+    let end = path.find("::")?;
+    let name = identifier_before(path, end)?;
+    Some(Crate {
+        name: name.to_string(),
+        disambiguator: None,
+    })
+}
+
+// The identifier ending at `end`, if there is one.
+fn identifier_before(text: &str, end: usize) -> Option<&str> {
+    let start = text[..end]
+        .rfind(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .map_or(0, |i| i + 1);
+    let name = &text[start..end];
+    (!name.is_empty() && !name.starts_with(|c: char| c.is_ascii_digit())).then_some(name)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolKind {
+    Function,
+    Object,
+    Other,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    /// The raw, still mangled, name.
+    pub name: String,
+    pub kind: SymbolKind,
+    pub address: u64,
+    pub size: u64,
+}
+
+/// Returns the defined symbols of the executable or object file at `path`.
+pub fn read(path: impl AsRef<Path>) -> Result<Vec<Symbol>, Error> {
+    parse(&fs::read(path)?)
+}
+
+/// Prefers the full symbol table, and falls back to the dynamic one for
+/// stripped executables.
+pub fn parse(data: &[u8]) -> Result<Vec<Symbol>, Error> {
+    let file = object::File::parse(data)?;
+    let mut symbols: Vec<Symbol> = file.symbols().filter_map(symbol).collect();
+    if symbols.is_empty() {
+        symbols = file.dynamic_symbols().filter_map(symbol).collect();
+    }
+    Ok(symbols)
+}
+
+fn symbol<'data>(symbol: impl ObjectSymbol<'data>) -> Option<Symbol> {
+    // Undefined symbols are provided by another file.
+    if symbol.is_undefined() {
+        return None;
+    }
+    Some(Symbol {
+        name: symbol
+            .name()
+            .ok()
+            .filter(|name| !name.is_empty())?
+            .to_string(),
+        kind: match symbol.kind() {
+            object::SymbolKind::Text => SymbolKind::Function,
+            object::SymbolKind::Data => SymbolKind::Object,
+            _ => SymbolKind::Other,
+        },
+        address: symbol.address(),
+        size: symbol.size(),
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrateCode {
+    pub krate: Crate,
+    /// The demangled paths of the crate's symbols.
+    pub symbols: Vec<String>,
+    /// The summed size of the crate's function symbols.
+    pub code_bytes: u64,
+}
+
+/// Groups `symbols` by defining crate, sorted by crate name and then by code
+/// size, largest first. Symbols that aren't Rust symbols are skipped.
+pub fn by_crate(symbols: &[Symbol]) -> Vec<CrateCode> {
+    let mut crates: BTreeMap<Crate, CrateCode> = BTreeMap::new();
+    // Aliases share an address; count their code once.
+    let mut seen = HashSet::new();
+    for symbol in symbols {
+        let Some(demangled) = demangle(&symbol.name) else {
+            continue;
+        };
+        let entry = crates
+            .entry(demangled.krate.clone())
+            .or_insert_with(|| CrateCode {
+                krate: demangled.krate,
+                symbols: Vec::new(),
+                code_bytes: 0,
+            });
+        entry.symbols.push(demangled.path);
+        if symbol.kind == SymbolKind::Function && seen.insert((symbol.address, symbol.size)) {
+            entry.code_bytes += symbol.size;
+        }
+    }
+    let mut crates: Vec<CrateCode> = crates.into_values().collect();
+    crates.sort_by(|a, b| {
+        a.krate
+            .name
+            .cmp(&b.krate.name)
+            .then(b.code_bytes.cmp(&a.code_bytes))
+    });
+    crates
+}
+
+/// Maps the disambiguator of every crate whose rlib is in `deps_dir` to the
+/// version it was compiled from. The dep-info files give each rlib's source
+/// and version (see [`depinfo::artifacts`], which resolves relative sources
+/// against `base`). The symbols an rlib defines for its own crate carry the
+/// disambiguator.
+pub fn versions(
+    deps_dir: impl AsRef<Path>,
+    base: impl AsRef<Path>,
+) -> Result<BTreeMap<Crate, Version>, Error> {
+    let deps_dir = deps_dir.as_ref();
+    let mut versions = BTreeMap::new();
+    for artifact in depinfo::artifacts(deps_dir, base)? {
+        let Some(version) = artifact.version else {
+            continue;
+        };
+        if !artifact.outputs.iter().any(|o| o == "rlib") {
+            continue;
+        }
+        let rlib = fs::read(deps_dir.join(format!("lib{}-{}.rlib", artifact.name, artifact.hash)))?;
+        if let Some(krate) = own_crate(&rlib, &artifact.name)? {
+            versions.insert(krate, version);
+        }
+    }
+    Ok(versions)
+}
+
+// The v0 crate of the first symbol an object file in `rlib` defines for
+// `name`.
+fn own_crate(rlib: &[u8], name: &str) -> Result<Option<Crate>, Error> {
+    for member in ArchiveFile::parse(rlib)?.members() {
+        let member = member?;
+        if !member.name().ends_with(b".o") {
+            continue;
+        }
+        let found = parse(member.data(rlib)?)?
+            .iter()
+            .filter_map(|symbol| demangle(&symbol.name))
+            .map(|demangled| demangled.krate)
+            .find(|krate| krate.name == name && krate.disambiguator.is_some());
+        if found.is_some() {
+            return Ok(found);
+        }
+    }
+    Ok(None)
+}
+
+/// Builds `bin` from the workspace at `dir`, with v0 symbol names, into
+/// `target_dir`. Returns the executable's path.
+pub fn build(dir: &Path, bin: &str, target_dir: &Path) -> Result<PathBuf, Error> {
+    // `CARGO_ENCODED_RUSTFLAGS` wins over `RUSTFLAGS` and any config, so the
+    // flag can't be dropped.
+    cargo::run_with_env(
+        dir,
+        &["build", "--quiet", "--bin", bin],
+        &[
+            ("CARGO_TARGET_DIR", target_dir.as_os_str()),
+            (
+                "CARGO_ENCODED_RUSTFLAGS",
+                OsStr::new("-Csymbol-mangling-version=v0"),
+            ),
+        ],
+    )?;
+    Ok(target_dir
+        .join("debug")
+        .join(format!("{bin}{}", env::consts::EXE_SUFFIX)))
+}
@@ -0,0 +1,226 @@
+//! Reads the per-crate compile times Cargo records in the HTML report written
+//! by `cargo build --timings`, and works out how much of them goes to crates
+//! that were built in more than one version.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::cargo;
+use crate::semver::Version;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Json(serde_json::Error),
+    Invalid(String),
+    Cargo(cargo::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "failed to read timings: {e}"),
+            Error::Json(e) => write!(f, "failed to parse timings: {e}"),
+            Error::Invalid(message) => write!(f, "invalid timings: {message}"),
+            Error::Cargo(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Json(e) => Some(e),
+            Error::Cargo(e) => Some(e),
+            Error::Invalid(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::Json(e)
+    }
+}
+
+impl From<cargo::Error> for Error {
+    fn from(e: cargo::Error) -> Self {
+        Error::Cargo(e)
+    }
+}
+
+/// One unit of work in the report: a crate's library, a build script, or a
+/// build script run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Unit {
+    pub name: String,
+    pub version: Version,
+    /// Empty for libraries, otherwise e.g. `" build-script (run)"`.
+    pub target: String,
+    /// Seconds since the build started.
+    pub start: f64,
+    pub duration: f64,
+}
+
+impl Unit {
+    pub fn end(&self) -> f64 {
+        self.start + self.duration
+    }
+}
+
+pub fn read(path: impl AsRef<Path>) -> Result<Vec<Unit>, Error> {
+    parse(&fs::read_to_string(path)?)
+}
+
+/// Extracts the units from a timings report. Cargo only lists the units it
+/// actually compiled, so crates that were fresh are missing.
+pub fn parse(html: &str) -> Result<Vec<Unit>, Error> {
+    let start = html
+        .find("const UNIT_DATA = ")
+        .ok_or_else(|| Error::Invalid("no `UNIT_DATA` in report".into()))?
+        + "const UNIT_DATA = ".len();
+    // Nothing inside the data (crate names, versions, features, target
+    // descriptions) can contain `];`, so the first one closes the array.
+    let end = html[start..]
+        .find("];")
+        .ok_or_else(|| Error::Invalid("unterminated `UNIT_DATA`".into()))?;
+    let data = serde_json::from_str::<serde_json::Value>(&html[start..start + end + 1])?;
+    data.as_array()
+        .ok_or_else(|| Error::Invalid("`UNIT_DATA` is not an array".into()))?
+        .iter()
+        .map(unit)
+        .collect()
+}
+
+fn unit(value: &serde_json::Value) -> Result<Unit, Error> {
+    let object = value
+        .as_object()
+        .ok_or_else(|| Error::Invalid("unit is not an object".into()))?;
+    let string = |key: &str| object.get(key).and_then(serde_json::Value::as_str);
+    let number = |key: &str| object.get(key).and_then(serde_json::Value::as_f64);
+    let name = string("name").ok_or_else(|| Error::Invalid("unit without a name".into()))?;
+    let missing = |key: &str| Error::Invalid(format!("unit `{name}` has no {key}"));
+    let version = string("version")
+        .ok_or_else(|| missing("version"))?
+        .parse()
+        .map_err(|e| Error::Invalid(format!("unit `{name}`: {e}")))?;
+    Ok(Unit {
+        name: name.to_string(),
+        version,
+        target: string("target").unwrap_or_default().to_string(),
+        start: number("start").ok_or_else(|| missing("start"))?,
+        duration: number("duration").ok_or_else(|| missing("duration"))?,
+    })
+}
+
+/// Splits the wall-clock time of the build between the units running at each
+/// moment: a unit that ran alone for a second gets the whole second, one of
+/// four units running side by side gets a quarter of it. The shares add up to
+/// the time anything was being built.
+pub fn wall_clock_shares(units: &[Unit]) -> Vec<f64> {
+    let mut times: Vec<f64> = units.iter().flat_map(|u| [u.start, u.end()]).collect();
+    times.sort_by(f64::total_cmp);
+    times.dedup();
+    let mut shares = vec![0.0; units.len()];
+    for window in times.windows(2) {
+        let (from, to) = (window[0], window[1]);
+        let running: Vec<usize> = (0..units.len())
+            .filter(|&i| units[i].start <= from && units[i].end() >= to)
+            .collect();
+        for &i in &running {
+            shares[i] += (to - from) / running.len() as f64;
+        }
+    }
+    shares
+}
+
+/// The time one version of a crate took, over all of its units.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Copy {
+    pub version: Version,
+    pub units: usize,
+    /// Seconds spent compiling, whatever else was running at the time.
+    pub seconds: f64,
+    /// This copy's part of the wall-clock time; see [`wall_clock_shares`].
+    pub wall_clock: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Duplicate {
+    pub name: String,
+    /// Lowest version first.
+    pub copies: Vec<Copy>,
+}
+
+impl Duplicate {
+    /// The copies that would go away if everything moved to the newest one.
+    pub fn extra(&self) -> &[Copy] {
+        &self.copies[..self.copies.len() - 1]
+    }
+
+    pub fn extra_seconds(&self) -> f64 {
+        self.extra().iter().map(|c| c.seconds).sum()
+    }
+
+    pub fn extra_wall_clock(&self) -> f64 {
+        self.extra().iter().map(|c| c.wall_clock).sum()
+    }
+}
+
+/// Returns one entry per crate that was compiled in more than one version,
+/// sorted by name.
+pub fn duplicates(units: &[Unit]) -> Vec<Duplicate> {
+    let shares = wall_clock_shares(units);
+    let mut by_name: BTreeMap<&str, BTreeMap<&Version, Copy>> = BTreeMap::new();
+    for (unit, share) in units.iter().zip(shares) {
+        let copy = by_name
+            .entry(&unit.name)
+            .or_default()
+            .entry(&unit.version)
+            .or_insert_with(|| Copy {
+                version: unit.version.clone(),
+                units: 0,
+                seconds: 0.0,
+                wall_clock: 0.0,
+            });
+        copy.units += 1;
+        copy.seconds += unit.duration;
+        copy.wall_clock += share;
+    }
+    by_name
+        .into_iter()
+        .filter(|(_, copies)| copies.len() > 1)
+        .map(|(name, copies)| Duplicate {
+            name: name.to_string(),
+            copies: copies.into_values().collect(),
+        })
+        .collect()
+}
+
+/// The time from the first unit starting to the last one finishing.
+pub fn wall_clock(units: &[Unit]) -> f64 {
+    let start = units.iter().map(|u| u.start).fold(f64::INFINITY, f64::min);
+    let end = units.iter().map(Unit::end).fold(0.0, f64::max);
+    (end - start).max(0.0)
+}
+
+/// Runs `cargo build --release --timings` in the workspace at `dir` from a
+/// clean target directory, so every crate shows up in the report.
+pub fn build(dir: &Path) -> Result<Vec<Unit>, Error> {
+    let target = dir.join("target");
+    if target.exists() {
+        fs::remove_dir_all(&target)?;
+    }
+    cargo::run(dir, &["build", "--release", "--timings", "--quiet"])?;
+    read(target.join("cargo-timings/cargo-timing.html"))
+}
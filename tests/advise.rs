@@ -0,0 +1,159 @@
+mod common;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use common::{run, stdout};
+use rust_incompatible_transitive_version_example::advise::{self, Change, Requirer};
+use rust_incompatible_transitive_version_example::graph::Graph;
+use rust_incompatible_transitive_version_example::lockfile::Lockfile;
+use rust_incompatible_transitive_version_example::manifest;
+use rust_incompatible_transitive_version_example::semver::{self, Version, VersionReq};
+
+#[test]
+fn version_requirements_follow_cargo() {
+    let matches = |req: &str, version: &str| {
+        req.parse::<VersionReq>()
+            .unwrap()
+            .matches(&version.parse().unwrap())
+    };
+    assert!(matches("0.3.9", "0.3.12"));
+    assert!(!matches("0.3.9", "0.4.0"));
+    assert!(!matches("0.3.9", "0.3.8"));
+    assert!(matches("1.2", "1.9.0"));
+    assert!(matches("0.0.3", "0.0.3"));
+    assert!(!matches("0.0.3", "0.0.4"));
+    assert!(matches("=0.3.8", "0.3.8"));
+    assert!(!matches("=0.3.8", "0.3.9"));
+    assert!(matches("~1.2.3", "1.2.9"));
+    assert!(!matches("~1.2.3", "1.3.0"));
+    assert!(matches(">=1.2, <1.5", "1.4.9"));
+    assert!(!matches(">=1.2, <1.5", "1.5.0"));
+    assert!(matches("1.*", "1.7.0"));
+    assert!(matches("*", "0.1.0"));
+    assert!(!matches("1.0", "1.1.0-beta.1"));
+    assert!(matches(">=1.1.0-beta.1", "1.1.0-beta.2"));
+    assert!("1.x.2".parse::<VersionReq>().is_err());
+
+    assert_eq!(
+        semver::compatible_requirement(&Version::new(0, 4, 22)),
+        "0.4"
+    );
+    assert_eq!(semver::compatible_requirement(&Version::new(2, 0, 0)), "2");
+    assert!(semver::is_compatible(
+        &Version::new(0, 4, 22),
+        &Version::new(0, 4, 0)
+    ));
+    assert!(!semver::is_compatible(
+        &Version::new(0, 3, 9),
+        &Version::new(0, 4, 22)
+    ));
+}
+
+#[test]
+fn paths_are_normalized_without_the_file_system() {
+    assert_eq!(
+        manifest::normalize(Path::new("./a/../fixtures/./x")),
+        PathBuf::from("fixtures/x")
+    );
+    assert_eq!(
+        manifest::normalize(Path::new("../vendor")),
+        PathBuf::from("../vendor")
+    );
+    assert_eq!(
+        manifest::relative(Path::new("a"), Path::new("fixtures/x")),
+        PathBuf::from("../fixtures/x")
+    );
+}
+
+fn write(path: &Path, contents: &str) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, contents).unwrap();
+}
+
+// `app` uses `a` and `b` from `crates/*`. `b` pins log 0.3 itself, and also
+// uses the registry crate `old`, which needs log 0.3 too. Its description
+// is a multi-line string.
+fn workspace(root: &Path) -> Lockfile {
+    let _ = fs::remove_dir_all(root);
+    write(
+        &root.join("Cargo.toml"),
+        "[package]\nname = \"app\"\nversion = \"0.1.0\"\n\
+         [dependencies]\na = { path = \"crates/a\" }\nb = { path = \"crates/b\" }\n\
+         [workspace]\nmembers = [\"crates/*\"]\n",
+    );
+    write(
+        &root.join("crates/a/Cargo.toml"),
+        "[package]\nname = \"a\"\nversion = \"0.1.0\"\n[dependencies]\nlog = \"0.4.22\"\n",
+    );
+    write(
+        &root.join("crates/b/Cargo.toml"),
+        "[package]\nname = \"b\"\nversion = \"0.1.0\"\n\
+         description = \"\"\"\nStill on log 0.3,\nthrough `old` too.\n\"\"\"\n\
+         [dependencies]\nold = \"1\"\n\
+         [target.'cfg(unix)'.dependencies]\nlogging = { package = \"log\", version = \"0.3.9\" }\n",
+    );
+    let registry = "source = \"registry+https://github.com/rust-lang/crates.io-index\"\n";
+    Lockfile::parse(&format!(
+        "[[package]]\nname = \"a\"\nversion = \"0.1.0\"\ndependencies = [\"log 0.4.22\"]\n\
+         [[package]]\nname = \"app\"\nversion = \"0.1.0\"\ndependencies = [\"a\", \"b\"]\n\
+         [[package]]\nname = \"b\"\nversion = \"0.1.0\"\ndependencies = [\"log 0.3.9\", \"old\"]\n\
+         [[package]]\nname = \"log\"\nversion = \"0.3.9\"\n{registry}\
+         [[package]]\nname = \"log\"\nversion = \"0.4.22\"\n{registry}\
+         [[package]]\nname = \"old\"\nversion = \"1.0.0\"\n{registry}dependencies = [\"log 0.3.9\"]\n"
+    ))
+    .unwrap()
+}
+
+#[test]
+fn suggests_manifest_edits_for_local_requirers_only() {
+    let root = Path::new(env!("CARGO_TARGET_TMPDIR")).join("advise");
+    let lockfile = workspace(&root);
+    let graph = Graph::new(&lockfile).unwrap();
+    let manifests = manifest::workspace(root.join("Cargo.toml")).unwrap();
+    let names: Vec<_> = manifests.iter().map(|m| m.name.as_deref()).collect();
+    assert_eq!(names, [Some("app"), Some("a"), Some("b")]);
+
+    let advice = advise::advise(&graph, &manifests);
+    assert_eq!(advice.len(), 1);
+    assert_eq!(advice[0].name, "log");
+    assert_eq!(advice[0].target.version, Version::new(0, 4, 22));
+    let [log_0_3] = &advice[0].outliers[..] else {
+        panic!("{advice:#?}");
+    };
+    assert_eq!(log_0_3.package.version, Version::new(0, 3, 9));
+    let [b, old] = &log_0_3.required_by[..] else {
+        panic!("{advice:#?}");
+    };
+    assert_eq!(
+        *b,
+        Requirer::Local {
+            package: graph.package(2),
+            manifest: root.join("crates/b/Cargo.toml"),
+            changes: vec![Change {
+                section: "target.'cfg(unix)'.dependencies".into(),
+                before: "logging = { package = \"log\", version = \"0.3.9\" }".into(),
+                after: "logging = { package = \"log\", version = \"0.4\" }".into(),
+            }],
+        }
+    );
+    let Requirer::Upstream { package, used_by } = old else {
+        panic!("{old:?}");
+    };
+    assert_eq!(package.name, "old");
+    let used_by: Vec<&str> = used_by.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(used_by, ["b"]);
+}
+
+#[test]
+fn advise_moves_b_to_log_0_4() {
+    let output = run(&["advise"]);
+    assert!(output.status.success(), "{output:?}");
+    let stdout = stdout(&output);
+    assert!(
+        stdout.contains(
+            "    b in b/Cargo.toml\n      [dependencies]\n      - log = \"0.3.9\"\n      + log = \"0.4\"\n"
+        ),
+        "{stdout}"
+    );
+}
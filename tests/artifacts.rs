@@ -0,0 +1,126 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use rust_incompatible_transitive_version_example::depinfo::{self, Rule};
+
+#[test]
+fn parses_rules_and_skips_comments() {
+    let rules = depinfo::parse(
+        "/t/deps/log-0123.d: src/lib.rs src/with\\ space.rs\n\
+         \n\
+         /t/deps/liblog-0123.rlib: src/lib.rs \\\n  src/macros.rs\n\
+         \n\
+         src/lib.rs:\n\
+         \n\
+         # env-dep:CARGO_PKG_NAME=log\n",
+    );
+    assert_eq!(
+        rules,
+        [
+            Rule {
+                target: "/t/deps/log-0123.d".into(),
+                prerequisites: vec!["src/lib.rs".into(), "src/with space.rs".into()],
+            },
+            Rule {
+                target: "/t/deps/liblog-0123.rlib".into(),
+                prerequisites: vec!["src/lib.rs".into(), "src/macros.rs".into()],
+            },
+        ]
+    );
+}
+
+fn write(path: &Path, contents: &str) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, contents).unwrap();
+}
+
+// Lays out a registry copy, a vendored copy and a path crate the way Cargo
+// would, plus a `deps` dir with two builds of the registry copy.
+fn fake_target(root: &Path) -> PathBuf {
+    let _ = fs::remove_dir_all(root);
+    let deps = root.join("target/debug/deps");
+    let registry = root.join("registry/log-0.4.22");
+    write(&registry.join("Cargo.toml"), "");
+    write(&root.join("vendor/log-0.3.8/Cargo.toml"), "");
+    write(
+        &root.join("local/Cargo.toml"),
+        "[package]\nname = \"local\"\nversion = \"1.2.3-beta.1\"\n",
+    );
+    for (hash, outputs) in [("aaaa", &["rlib", "rmeta"][..]), ("bbbb", &["rmeta"])] {
+        let lib = registry.join("src/lib.rs");
+        let mut contents = String::new();
+        for output in outputs {
+            contents += &format!("/t/liblog-{hash}.{output}: {}\n\n", lib.display());
+        }
+        write(&deps.join(format!("log-{hash}.d")), &contents);
+    }
+    write(
+        &deps.join("log-cccc.d"),
+        "/t/liblog-cccc.rlib: vendor/log-0.3.8/src/lib.rs\n",
+    );
+    write(
+        &deps.join("local-dddd.d"),
+        "/t/liblocal-dddd.rlib: local/src/lib.rs\n",
+    );
+    write(
+        &deps.join("build_script_build-eeee.d"),
+        "/t/build_script_build-eeee: local/build.rs\n",
+    );
+    deps
+}
+
+#[test]
+fn reports_crates_built_from_more_than_one_source() {
+    let root = Path::new(env!("CARGO_TARGET_TMPDIR")).join("depinfo");
+    let deps = fake_target(&root);
+    let artifacts = depinfo::artifacts(&deps, &root).unwrap();
+    let summary: Vec<_> = artifacts
+        .iter()
+        .map(|a| {
+            (
+                a.name.as_str(),
+                a.hash.as_str(),
+                a.version.as_ref().map(ToString::to_string),
+                a.outputs.join(","),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            ("local", "dddd", Some("1.2.3-beta.1".into()), "rlib".into()),
+            ("log", "aaaa", Some("0.4.22".into()), "rlib,rmeta".into()),
+            ("log", "bbbb", Some("0.4.22".into()), "rmeta".into()),
+            ("log", "cccc", Some("0.3.8".into()), "rlib".into()),
+        ]
+    );
+
+    let duplicates = depinfo::duplicates(&artifacts);
+    assert_eq!(duplicates.len(), 1);
+    let log = &duplicates[0];
+    assert_eq!(log.name, "log");
+    assert_eq!(log.sources.len(), 2);
+    assert_eq!(log.sources[0].dir, Path::new("vendor/log-0.3.8"));
+    assert_eq!(log.sources[1].dir, root.join("registry/log-0.4.22"));
+    let hashes: Vec<_> = log.sources[1].artifacts.iter().map(|a| &a.hash).collect();
+    assert_eq!(hashes, ["aaaa", "bbbb"]);
+}
+
+#[test]
+fn the_workspace_build_compiled_log_three_times() {
+    let deps = Path::new(env!(
+        "CARGO_BIN_EXE_rust-incompatible-transitive-version-example"
+    ))
+    .parent()
+    .unwrap()
+    .join("deps");
+    let artifacts = depinfo::artifacts(&deps, env!("CARGO_MANIFEST_DIR")).unwrap();
+    let duplicates = depinfo::duplicates(&artifacts);
+    let log = duplicates.iter().find(|d| d.name == "log").unwrap();
+    let versions: Vec<_> = log
+        .sources
+        .iter()
+        .map(|s| s.version.unwrap().to_string())
+        .collect();
+    assert_eq!(versions, ["0.3.8", "0.3.9", "0.4.22"]);
+}
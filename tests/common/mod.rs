@@ -0,0 +1,17 @@
+use std::process::{Command, Output};
+
+/// Runs the example binary from the workspace root, without colours.
+pub fn run(args: &[&str]) -> Output {
+    Command::new(env!(
+        "CARGO_BIN_EXE_rust-incompatible-transitive-version-example"
+    ))
+    .args(args)
+    .current_dir(env!("CARGO_MANIFEST_DIR"))
+    .env("NO_COLOR", "1")
+    .output()
+    .expect("failed to run the example binary")
+}
+
+pub fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).expect("stdout is not UTF-8")
+}
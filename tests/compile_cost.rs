@@ -0,0 +1,52 @@
+use rust_incompatible_transitive_version_example::semver::Version;
+use rust_incompatible_transitive_version_example::timings;
+
+// The shape of the script Cargo embeds in `cargo-timing.html`, trimmed down.
+const REPORT: &str = r#"<html><script>
+const UNIT_DATA = [
+  {"i": 0, "name": "log", "version": "0.4.22", "mode": "todo", "target": "",
+   "features": ["std"], "start": 0.0, "duration": 2.0,
+   "unblocked_units": [], "unblocked_rmeta_units": [1], "sections": null},
+  {"i": 1, "name": "log", "version": "0.3.9", "mode": "todo", "target": "",
+   "features": ["default", "use_std"], "start": 1.0, "duration": 1.0,
+   "unblocked_units": [], "unblocked_rmeta_units": []},
+  {"i": 2, "name": "b", "version": "0.1.0", "mode": "todo", "target": "",
+   "start": 2.0, "duration": 0.5, "unblocked_units": []},
+  {"i": 3, "name": "log", "version": "0.3.9", "mode": "run-custom-build",
+   "target": " build-script (run)", "start": 2.5, "duration": 0.5}
+];
+const CONCURRENCY_DATA = [];
+</script></html>"#;
+
+#[test]
+fn reads_units_from_the_timings_report() {
+    let units = timings::parse(REPORT).unwrap();
+    assert_eq!(units.len(), 4);
+    assert_eq!(units[0].name, "log");
+    assert_eq!(units[0].version, Version::new(0, 4, 22));
+    assert_eq!(units[3].target, " build-script (run)");
+    assert_eq!(units[3].end(), 3.0);
+    assert!(timings::parse("<html></html>").is_err());
+}
+
+#[test]
+fn splits_wall_clock_between_concurrent_units() {
+    let units = timings::parse(REPORT).unwrap();
+    // log 0.4.22 runs alone for a second, then shares the next with log 0.3.9.
+    assert_eq!(timings::wall_clock_shares(&units), [1.5, 0.5, 0.5, 0.5]);
+    assert_eq!(timings::wall_clock(&units), 3.0);
+}
+
+#[test]
+fn attributes_older_copies_as_extra() {
+    let units = timings::parse(REPORT).unwrap();
+    let duplicates = timings::duplicates(&units);
+    assert_eq!(duplicates.len(), 1);
+    let log = &duplicates[0];
+    assert_eq!(log.name, "log");
+    let versions: Vec<String> = log.copies.iter().map(|c| c.version.to_string()).collect();
+    assert_eq!(versions, ["0.3.9", "0.4.22"]);
+    assert_eq!(log.copies[0].units, 2);
+    assert_eq!(log.extra_seconds(), 1.5);
+    assert_eq!(log.extra_wall_clock(), 1.0);
+}
@@ -0,0 +1,7 @@
+// Each crate's `log` types are distinct types to rustc, even when the names
+// match. The expected compiler errors are snapshotted next to each case in
+// `tests/ui`; regenerate them with `TRYBUILD=overwrite cargo test`.
+#[test]
+fn log_types_do_not_cross_versions() {
+    trybuild::TestCases::new().compile_fail("tests/ui/*.rs");
+}
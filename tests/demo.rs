@@ -0,0 +1,110 @@
+mod common;
+
+use std::path::Path;
+
+use common::{run, stdout};
+use rust_incompatible_transitive_version_example::duplicates;
+use rust_incompatible_transitive_version_example::lockfile::Lockfile;
+
+#[test]
+fn each_crate_logs_through_its_own_log_version() {
+    let output = run(&[]);
+    assert!(output.status.success(), "{output:?}");
+    let stdout = stdout(&output);
+
+    let a = stdout
+        .lines()
+        .find(|line| line.contains("logged using log@0.4"))
+        .unwrap_or_else(|| panic!("no record from `a` in:\n{stdout}"));
+    assert!(a.ends_with("INFO  [a] logged using log@0.4.22"), "{a}");
+
+    let b = stdout
+        .lines()
+        .find(|line| line.contains("logged using log@0.3"))
+        .unwrap_or_else(|| panic!("no record from `b` in:\n{stdout}"));
+    assert!(b.ends_with("INFO  [b] logged using log@0.3.9"), "{b}");
+}
+
+#[test]
+fn provenance_table_lists_both_versions() {
+    let stdout = stdout(&run(&[]));
+    let rows: Vec<Vec<&str>> = stdout
+        .lines()
+        .skip_while(|line| !line.starts_with("crate"))
+        .skip(1)
+        .map(|line| line.split_whitespace().collect())
+        .collect();
+    assert_eq!(
+        rows,
+        [
+            ["a", "0.4.22", "TRACE", "true"],
+            ["b", "0.3.9", "TRACE", "true"],
+        ]
+    );
+}
+
+#[test]
+fn lockfile_contains_three_log_versions() {
+    let lockfile = Lockfile::read(Path::new(env!("CARGO_MANIFEST_DIR")).join("Cargo.lock"))
+        .expect("failed to read Cargo.lock");
+    let duplicates = duplicates::find(&lockfile);
+    let names: Vec<&str> = duplicates.iter().map(|d| d.name).collect();
+    assert_eq!(names, ["log", "plugins"]);
+
+    let log = &duplicates[0];
+    let versions: Vec<(String, bool)> = log
+        .packages
+        .iter()
+        .map(|p| (p.version.to_string(), p.source.is_some()))
+        .collect();
+    // 0.3.8 is the vendored path copy used by `c`.
+    assert_eq!(
+        versions,
+        [
+            ("0.3.8".to_string(), false),
+            ("0.3.9".to_string(), true),
+            ("0.4.22".to_string(), true),
+        ]
+    );
+}
+
+#[test]
+fn duplicates_deny_fails_on_the_log_split() {
+    let output = run(&["duplicates", "--deny"]);
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = stdout(&output);
+    assert!(stdout.starts_with("log\n  0.3.8\n  0.3.9 ("), "{stdout}");
+}
+
+#[test]
+fn semver_trick_drops_records_from_log_0_3_8() {
+    let output = run(&["semver-trick"]);
+    assert!(output.status.success(), "{output:?}");
+    let stdout = stdout(&output);
+    assert!(
+        stdout.contains("INFO  [b] logged using log@0.3.9"),
+        "{stdout}"
+    );
+    assert!(!stdout.contains("logged using log@0.3.8"), "{stdout}");
+    assert!(
+        stdout
+            .lines()
+            .any(|line| line.split_whitespace().eq(["c", "0.3.8", "OFF", "false"])),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn each_plugins_copy_keeps_its_own_registry() {
+    let output = run(&["globals"]);
+    assert!(output.status.success(), "{output:?}");
+    let stdout = stdout(&output);
+    assert!(
+        stdout.contains("plugins seen by a: [\"json\"]\n"),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("plugins seen by b: [\"yaml\"]\n"),
+        "{stdout}"
+    );
+}
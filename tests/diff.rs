@@ -0,0 +1,120 @@
+mod common;
+
+use common::{run, stdout};
+use rust_incompatible_transitive_version_example::diff;
+use rust_incompatible_transitive_version_example::lockfile::{Lockfile, Package};
+
+fn lockfile(packages: &[(&str, &str)]) -> Lockfile {
+    let input: String = packages
+        .iter()
+        .map(|(name, version)| format!("[[package]]\nname = \"{name}\"\nversion = \"{version}\"\n"))
+        .collect();
+    Lockfile::parse(&input).unwrap()
+}
+
+fn names(packages: &[&Package]) -> Vec<String> {
+    packages.iter().map(ToString::to_string).collect()
+}
+
+#[test]
+fn reports_added_removed_and_bumped_packages() {
+    let old = lockfile(&[("a", "0.1.0"), ("log", "0.3.8"), ("time", "0.3.36")]);
+    let new = lockfile(&[("a", "0.1.0"), ("log", "0.3.9"), ("serde", "1.0.229")]);
+    let diff = diff::diff(&old, &new);
+    assert_eq!(names(&diff.added), ["serde 1.0.229"]);
+    assert_eq!(names(&diff.removed), ["time 0.3.36"]);
+    let bumped: Vec<String> = diff
+        .bumped
+        .iter()
+        .map(|(old, new)| format!("{old} → {}", new.version))
+        .collect();
+    assert_eq!(bumped, ["log 0.3.8 → 0.3.9"]);
+    assert!(diff.duplicates.is_empty());
+    assert!(diff::diff(&old, &old).is_empty());
+}
+
+#[test]
+fn duplicates_bump_only_within_a_compatible_range() {
+    let old = lockfile(&[("log", "0.4.21")]);
+    let new = lockfile(&[("log", "0.3.9"), ("log", "0.4.22")]);
+    let diff = diff::diff(&old, &new);
+    let bumped: Vec<String> = diff
+        .bumped
+        .iter()
+        .map(|(old, new)| format!("{old} → {}", new.version))
+        .collect();
+    assert_eq!(bumped, ["log 0.4.21 → 0.4.22"]);
+    assert_eq!(names(&diff.added), ["log 0.3.9"]);
+    assert!(diff.removed.is_empty());
+}
+
+#[test]
+fn a_single_version_on_each_side_is_a_bump_across_ranges() {
+    let old = lockfile(&[("log", "0.3.9"), ("time", "0.1.45")]);
+    let new = lockfile(&[("log", "0.4.22"), ("time", "0.3.36")]);
+    let diff = diff::diff(&old, &new);
+    let bumped: Vec<String> = diff
+        .bumped
+        .iter()
+        .map(|(old, new)| format!("{old} → {}", new.version))
+        .collect();
+    assert_eq!(bumped, ["log 0.3.9 → 0.4.22", "time 0.1.45 → 0.3.36"]);
+    assert!(diff.added.is_empty());
+    assert!(diff.removed.is_empty());
+}
+
+#[test]
+fn reports_duplicates_that_appear_change_or_go_away() {
+    let old = lockfile(&[
+        ("log", "0.4.22"),
+        ("plugins", "1.0.0"),
+        ("plugins", "2.0.0"),
+        ("serde", "1.0.1"),
+        ("serde", "1.0.2"),
+    ]);
+    let new = lockfile(&[
+        ("log", "0.3.9"),
+        ("log", "0.4.22"),
+        ("plugins", "2.0.0"),
+        ("serde", "1.0.1"),
+        ("serde", "1.0.3"),
+    ]);
+    let diff = diff::diff(&old, &new);
+    // Only the extra log counts as added; the new serde replaced the old one.
+    assert_eq!(names(&diff.added), ["log 0.3.9"]);
+    assert_eq!(names(&diff.removed), ["plugins 1.0.0"]);
+    assert_eq!(diff.bumped.len(), 1);
+
+    let changes: Vec<String> = diff
+        .duplicates
+        .iter()
+        .map(|c| {
+            format!(
+                "{} appeared={} resolved={}: {:?} → {:?}",
+                c.name,
+                c.appeared(),
+                c.resolved(),
+                names(&c.old),
+                names(&c.new)
+            )
+        })
+        .collect();
+    assert_eq!(
+        changes,
+        [
+            "log appeared=true resolved=false: [\"log 0.4.22\"] → [\"log 0.3.9\", \"log 0.4.22\"]",
+            "plugins appeared=false resolved=true: [\"plugins 1.0.0\", \"plugins 2.0.0\"] → [\"plugins 2.0.0\"]",
+            "serde appeared=false resolved=false: [\"serde 1.0.1\", \"serde 1.0.2\"] → [\"serde 1.0.1\", \"serde 1.0.3\"]",
+        ]
+    );
+}
+
+#[test]
+fn diff_of_a_lockfile_with_itself_is_empty() {
+    let output = run(&["diff", "Cargo.lock", "Cargo.lock"]);
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(
+        stdout(&output),
+        "Cargo.lock and Cargo.lock lock the same packages\n"
+    );
+}
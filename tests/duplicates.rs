@@ -0,0 +1,75 @@
+use rust_incompatible_transitive_version_example::duplicates;
+use rust_incompatible_transitive_version_example::lockfile::Lockfile;
+
+const REGISTRY: &str = "registry+https://github.com/rust-lang/crates.io-index";
+
+#[test]
+fn finds_every_name_locked_more_than_once() {
+    let lockfile = Lockfile::parse(&format!(
+        "version = 3\n\
+         [[package]]\nname = \"app\"\nversion = \"0.1.0\"\n\
+         [[package]]\nname = \"log\"\nversion = \"0.4.22\"\nsource = \"{REGISTRY}\"\n\
+         [[package]]\nname = \"log\"\nversion = \"0.3.9\"\nsource = \"{REGISTRY}\"\n\
+         [[package]]\nname = \"log\"\nversion = \"0.3.8\"\n\
+         [[package]]\nname = \"cfg-if\"\nversion = \"1.0.0\"\nsource = \"{REGISTRY}\"\n\
+         [[package]]\nname = \"bitflags\"\nversion = \"2.6.0\"\nsource = \"{REGISTRY}\"\n\
+         [[package]]\nname = \"bitflags\"\nversion = \"1.3.2\"\nsource = \"{REGISTRY}\"\n"
+    ))
+    .unwrap();
+    let duplicates = duplicates::find(&lockfile);
+    let found: Vec<(&str, Vec<String>)> = duplicates
+        .iter()
+        .map(|d| {
+            let versions = d.packages.iter().map(|p| p.version.to_string()).collect();
+            (d.name, versions)
+        })
+        .collect();
+    assert_eq!(
+        found,
+        [
+            ("bitflags", vec!["1.3.2".to_string(), "2.6.0".to_string()]),
+            (
+                "log",
+                vec![
+                    "0.3.8".to_string(),
+                    "0.3.9".to_string(),
+                    "0.4.22".to_string()
+                ]
+            ),
+        ]
+    );
+}
+
+#[test]
+fn the_same_version_from_two_sources_is_a_duplicate() {
+    let lockfile = Lockfile::parse(&format!(
+        "version = 3\n\
+         [[package]]\nname = \"log\"\nversion = \"0.4.22\"\nsource = \"{REGISTRY}\"\n\
+         [[package]]\nname = \"log\"\nversion = \"0.4.22\"\n\
+         source = \"git+https://github.com/rust-lang/log#0123456\"\n"
+    ))
+    .unwrap();
+    let [log] = &duplicates::find(&lockfile)[..] else {
+        panic!("expected one duplicate");
+    };
+    let sources: Vec<&str> = log
+        .packages
+        .iter()
+        .map(|p| p.source.as_deref().unwrap())
+        .collect();
+    assert_eq!(
+        sources,
+        ["git+https://github.com/rust-lang/log#0123456", REGISTRY]
+    );
+}
+
+#[test]
+fn a_lockfile_without_duplicates_reports_nothing() {
+    let lockfile = Lockfile::parse(
+        "version = 3\n\
+         [[package]]\nname = \"app\"\nversion = \"0.1.0\"\ndependencies = [\"log\"]\n\
+         [[package]]\nname = \"log\"\nversion = \"0.4.22\"\n",
+    )
+    .unwrap();
+    assert!(duplicates::find(&lockfile).is_empty());
+}
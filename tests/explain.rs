@@ -0,0 +1,207 @@
+mod common;
+
+use common::{run, stdout};
+use rust_incompatible_transitive_version_example::explain::{self, Fix};
+use rust_incompatible_transitive_version_example::resolve::{Dependency, Index, Pip, Strategy};
+
+fn dependencies(deps: &[(&str, &str)]) -> Vec<Dependency> {
+    deps.iter()
+        .map(|(name, req)| Dependency::new(*name, req.parse().unwrap()))
+        .collect()
+}
+
+// Name, version and dependencies.
+type Release<'a> = (&'a str, &'a str, &'a [(&'a str, &'a str)]);
+
+fn index(releases: &[Release]) -> Index {
+    let mut index = Index::new();
+    for (name, version, deps) in releases {
+        index.add(name, version.parse().unwrap(), dependencies(deps));
+    }
+    index
+}
+
+fn report(index: &Index, root: &[Dependency]) -> String {
+    let conflict = Pip.resolve(index, root).unwrap_err();
+    explain::explain(index, root, &conflict).to_string()
+}
+
+#[test]
+fn converts_requirements_to_pep_440_specifiers() {
+    let cases = [
+        ("0.3.9", ">=0.3.9,<0.4"),
+        ("^0.4.22", ">=0.4.22,<0.5"),
+        ("1.2", ">=1.2,<2"),
+        ("0.0.3", ">=0.0.3,<0.0.4"),
+        ("~1.2.3", ">=1.2.3,<1.3"),
+        ("=1.0.0", "==1.0.0"),
+        ("=1.0", "==1.0.*"),
+        ("1.*", "==1.*"),
+        ("*", ""),
+        (">1.2", ">=1.3"),
+        ("<=1.2", "<1.3"),
+        (">=0.15, <2.0", ">=0.15,<2.0"),
+    ];
+    for (req, specifier) in cases {
+        assert_eq!(
+            explain::specifier(&req.parse().unwrap()).as_deref(),
+            Some(specifier),
+            "{req}"
+        );
+    }
+}
+
+#[test]
+fn reports_the_log_split_like_pip() {
+    let index = index(&[
+        ("app", "0.1.0", &[("a", "0.1"), ("b", "0.1")]),
+        ("a", "0.1.0", &[("log", "0.4.22")]),
+        ("b", "0.1.0", &[("log", "0.3.9")]),
+        ("log", "0.3.9", &[("log", "0.4")]),
+        ("log", "0.4.22", &[]),
+    ]);
+    let root = dependencies(&[("app", "0.1")]);
+    assert_eq!(
+        report(&index, &root),
+        "\
+ERROR: Cannot install app>=0.1,<0.2 because these package versions have conflicting dependencies.
+
+The conflict is caused by:
+    b 0.1.0 depends on log>=0.3.9,<0.4
+    a 0.1.0 depends on log>=0.4.22,<0.5
+
+To fix this you could try to:
+1. update b 0.1.0 to accept log 0.4.22, or stop depending on b
+2. loosen the range of package versions you've specified
+3. remove package versions to allow pip to attempt to solve the dependency conflict
+
+ERROR: ResolutionImpossible: for help visit https://pip.pypa.io/en/latest/topics/dependency-resolution/#dealing-with-dependency-conflicts
+"
+    );
+}
+
+#[test]
+fn suggests_loosening_the_users_own_pin() {
+    let index = index(&[
+        ("flask", "2.0.0", &[("werkzeug", ">=2.0")]),
+        ("werkzeug", "1.0.0", &[]),
+        ("werkzeug", "2.0.0", &[]),
+    ]);
+    let root = dependencies(&[("flask", "=2.0.0"), ("werkzeug", "=1.0.0")]);
+    let conflict = Pip.resolve(&index, &root).unwrap_err();
+    let explanation = explain::explain(&index, &root, &conflict);
+    assert_eq!(explanation.requested, root);
+    assert_eq!(
+        explanation.fixes,
+        [
+            Fix::Update {
+                name: "flask".into(),
+                from: "2.0.0".parse().unwrap(),
+                package: "werkzeug".into(),
+                version: "1.0.0".parse().unwrap(),
+            },
+            Fix::Loosen {
+                dependency: root[1].clone(),
+                to: "2.0.0".parse().unwrap(),
+            },
+        ]
+    );
+    let report = explanation.to_string();
+    assert!(
+        report.starts_with(
+            "ERROR: Cannot install flask==2.0.0 and werkzeug==1.0.0 because these package \
+             versions have conflicting dependencies.\n"
+        ),
+        "{report}"
+    );
+    assert!(
+        report.contains(
+            "    flask 2.0.0 depends on werkzeug>=2.0\n    The user requested werkzeug==1.0.0\n"
+        ),
+        "{report}"
+    );
+    assert!(
+        report.contains(
+            "2. loosen your requirement werkzeug==1.0.0 so that it accepts werkzeug 2.0.0\n"
+        ),
+        "{report}"
+    );
+}
+
+#[test]
+fn suggests_another_release_of_a_dependent() {
+    let index = index(&[
+        ("a", "0.1.0", &[("log", "0.4")]),
+        ("b", "0.1.0", &[("log", "0.3")]),
+        ("b", "0.2.0", &[("log", "0.4")]),
+        ("log", "0.3.9", &[]),
+        ("log", "0.4.22", &[]),
+    ]);
+    // Pinning b keeps pip from finding b 0.2.0 on its own.
+    let root = dependencies(&[("a", "0.1"), ("b", "=0.1.0")]);
+    let conflict = Pip.resolve(&index, &root).unwrap_err();
+    let explanation = explain::explain(&index, &root, &conflict);
+    assert!(
+        explanation.fixes.contains(&Fix::Switch {
+            name: "b".into(),
+            from: "0.1.0".parse().unwrap(),
+            to: "0.2.0".parse().unwrap(),
+        }),
+        "{explanation}"
+    );
+    assert!(explanation
+        .to_string()
+        .contains(". use b 0.2.0 instead of b 0.1.0\n"));
+}
+
+#[test]
+fn skips_releases_whose_own_dependencies_cannot_be_met() {
+    let index = index(&[
+        ("a", "0.1.0", &[("log", "0.4")]),
+        ("b", "0.1.0", &[("log", "0.3")]),
+        ("b", "0.2.0", &[("log", "0.4"), ("time", "0.2")]),
+        ("log", "0.3.9", &[("log", "0.4")]),
+        ("log", "0.4.22", &[]),
+        ("time", "0.1.45", &[]),
+    ]);
+    let root = dependencies(&[("a", "0.1"), ("b", "=0.1.0")]);
+    let conflict = Pip.resolve(&index, &root).unwrap_err();
+    let explanation = explain::explain(&index, &root, &conflict);
+    // b 0.2.0 needs a time that doesn't exist, and log 0.3.9 needs log 0.4
+    // next to itself.
+    assert_eq!(
+        explanation.fixes,
+        [Fix::Update {
+            name: "b".into(),
+            from: "0.1.0".parse().unwrap(),
+            package: "log".into(),
+            version: "0.4.22".parse().unwrap(),
+        }]
+    );
+}
+
+#[test]
+fn reports_a_requirement_nothing_matches() {
+    let index = index(&[("a", "0.1.0", &[("log", "0.5")]), ("log", "0.4.22", &[])]);
+    let root = dependencies(&[("a", "0.1")]);
+    assert_eq!(
+        report(&index, &root),
+        "\
+ERROR: Could not find a version that satisfies the requirement log>=0.5,<0.6 (from a==0.1.0)
+ERROR: No matching distribution found for log
+"
+    );
+}
+
+#[test]
+fn pip_check_explains_the_log_conflict() {
+    let output = run(&["pip-check"]);
+    assert!(!output.status.success(), "{output:?}");
+    let stdout = stdout(&output);
+    assert!(
+        stdout.contains(
+            "    b 0.1.0 depends on log>=0.3.9,<0.4\n    a 0.1.0 depends on log>=0.4.22,<0.5\n"
+        ),
+        "{stdout}"
+    );
+}
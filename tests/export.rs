@@ -0,0 +1,77 @@
+use rust_incompatible_transitive_version_example::export::{self, View};
+use rust_incompatible_transitive_version_example::graph::Graph;
+use rust_incompatible_transitive_version_example::lockfile::Lockfile;
+
+// The README's demo: `b` pulls in log 0.3.9, which forwards to log 0.4.22.
+const LOCKFILE: &str = "\
+[[package]]
+name = \"a\"
+version = \"0.1.0\"
+dependencies = [\"log 0.4.22\"]
+
+[[package]]
+name = \"app\"
+version = \"0.1.0\"
+dependencies = [\"a\", \"b\", \"time\"]
+
+[[package]]
+name = \"b\"
+version = \"0.1.0\"
+dependencies = [\"log 0.3.9\"]
+
+[[package]]
+name = \"log\"
+version = \"0.3.9\"
+dependencies = [\"log 0.4.22\"]
+
+[[package]]
+name = \"log\"
+version = \"0.4.22\"
+
+[[package]]
+name = \"time\"
+version = \"0.3.36\"
+";
+
+#[test]
+fn mermaid_highlights_duplicates_and_the_edges_into_them() {
+    let lockfile = Lockfile::parse(LOCKFILE).unwrap();
+    let graph = Graph::new(&lockfile).unwrap();
+    assert_eq!(
+        export::mermaid(&graph, &View::full(&graph)),
+        "graph TD\n\
+         \x20   n0[\"a\"]\n\
+         \x20   n1[\"app\"]\n\
+         \x20   n2[\"b\"]\n\
+         \x20   n3[\"log 0.3.9\"]\n\
+         \x20   n4[\"log 0.4.22\"]\n\
+         \x20   n5[\"time\"]\n\
+         \x20   n0 --> n4\n\
+         \x20   n1 --> n0\n\
+         \x20   n1 --> n2\n\
+         \x20   n1 --> n5\n\
+         \x20   n2 --> n3\n\
+         \x20   n3 --> n4\n\
+         \x20   classDef duplicate fill:#ffdddd,stroke:#cc0000\n\
+         \x20   class n3,n4 duplicate\n\
+         \x20   linkStyle 0,4,5 stroke:#cc0000\n"
+    );
+}
+
+#[test]
+fn dot_can_leave_out_packages_unrelated_to_duplicates() {
+    let lockfile = Lockfile::parse(LOCKFILE).unwrap();
+    let graph = Graph::new(&lockfile).unwrap();
+    let view = View::duplicates_only(&graph);
+    assert_eq!(view.nodes, [0, 1, 2, 3, 4]);
+
+    let dot = export::dot(&graph, &view);
+    assert!(dot.starts_with("digraph dependencies {\n"), "{dot}");
+    assert!(!dot.contains("time"), "{dot}");
+    assert!(
+        dot.contains("    n3 [label=\"log 0.3.9\", color=\"#cc0000\","),
+        "{dot}"
+    );
+    assert!(dot.contains("    n2 -> n3 [color=\"#cc0000\"];\n"), "{dot}");
+    assert!(dot.contains("    n1 -> n2;\n"), "{dot}");
+}
@@ -0,0 +1,41 @@
+use std::path::Path;
+
+use rust_incompatible_transitive_version_example::features::{self, Seen};
+
+fn seen(resolver: &str) -> Vec<Seen> {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR"))
+        .join("features")
+        .join(format!("resolver-{resolver}"));
+    features::write(&dir, resolver).unwrap();
+    features::build(&dir).unwrap()
+}
+
+fn expected(rows: &[(&str, &[&str])]) -> Vec<Seen> {
+    rows.iter()
+        .map(|(by, features)| Seen {
+            by: by.to_string(),
+            features: features.iter().map(|f| f.to_string()).collect(),
+        })
+        .collect()
+}
+
+#[test]
+fn resolver_1_unifies_every_feature_everywhere() {
+    let all: &[&str] = &["build", "never", "x", "y"];
+    assert_eq!(
+        seen("1"),
+        expected(&[("a", all), ("b", all), ("b (build script)", all)])
+    );
+}
+
+#[test]
+fn resolver_2_unifies_normal_dependencies_only() {
+    assert_eq!(
+        seen("2"),
+        expected(&[
+            ("a", &["x", "y"]),
+            ("b", &["x", "y"]),
+            ("b (build script)", &["build"]),
+        ])
+    );
+}
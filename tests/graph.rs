@@ -0,0 +1,112 @@
+use rust_incompatible_transitive_version_example::graph::Graph;
+use rust_incompatible_transitive_version_example::lockfile::Lockfile;
+
+const REGISTRY: &str = "registry+https://github.com/rust-lang/crates.io-index";
+const GIT: &str = "git+https://github.com/rust-lang/log#0123456";
+
+// `app` uses `a` and `b`. `a` needs log 0.4.22 from the registry and `b` the
+// same version from git, so their entries carry the source. Both also use
+// `cfg-if`, the only one locked, which is written by name alone. log 0.3.9
+// is a plain `name version` entry, and forwards to the registry log 0.4.22.
+fn lockfile() -> Lockfile {
+    Lockfile::parse(&format!(
+        "version = 3\n\
+         [[package]]\nname = \"a\"\nversion = \"0.1.0\"\n\
+         dependencies = [\"cfg-if\", \"log 0.4.22 ({REGISTRY})\"]\n\
+         [[package]]\nname = \"app\"\nversion = \"0.1.0\"\n\
+         dependencies = [\"a\", \"b\", \"log 0.3.9\"]\n\
+         [[package]]\nname = \"b\"\nversion = \"0.1.0\"\n\
+         dependencies = [\"cfg-if\", \"log 0.4.22 ({GIT})\"]\n\
+         [[package]]\nname = \"cfg-if\"\nversion = \"1.0.0\"\nsource = \"{REGISTRY}\"\n\
+         [[package]]\nname = \"log\"\nversion = \"0.3.9\"\nsource = \"{REGISTRY}\"\n\
+         dependencies = [\"log 0.4.22 ({REGISTRY})\"]\n\
+         [[package]]\nname = \"log\"\nversion = \"0.4.22\"\nsource = \"{REGISTRY}\"\n\
+         [[package]]\nname = \"log\"\nversion = \"0.4.22\"\nsource = \"{GIT}\"\n"
+    ))
+    .unwrap()
+}
+
+fn names(graph: &Graph<'_>, ids: &[usize]) -> Vec<String> {
+    ids.iter()
+        .map(|&id| {
+            let package = graph.package(id);
+            match package.source.as_deref() {
+                Some(GIT) => format!("{package} (git)"),
+                _ => package.to_string(),
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn resolves_every_form_of_dependency_entry() {
+    let lockfile = lockfile();
+    let graph = Graph::new(&lockfile).unwrap();
+    let id = |name: &str| graph.find(name, None).unwrap();
+    assert_eq!(
+        names(&graph, graph.dependencies(id("a"))),
+        ["cfg-if 1.0.0", "log 0.4.22"]
+    );
+    assert_eq!(
+        names(&graph, graph.dependencies(id("b"))),
+        ["cfg-if 1.0.0", "log 0.4.22 (git)"]
+    );
+    assert_eq!(
+        names(&graph, graph.dependencies(id("app"))),
+        ["a 0.1.0", "b 0.1.0", "log 0.3.9"]
+    );
+    assert_eq!(
+        names(&graph, graph.dependents(id("cfg-if"))),
+        ["a 0.1.0", "b 0.1.0"]
+    );
+    assert_eq!(names(&graph, &graph.roots()), ["app 0.1.0"]);
+
+    // Two log 0.4.22 packages are locked, so the version alone is ambiguous.
+    assert_eq!(graph.find("log", Some("0.4.22")), None);
+    assert_eq!(graph.find("log", None), None);
+}
+
+#[test]
+fn lists_every_path_between_two_packages() {
+    let lockfile = lockfile();
+    let graph = Graph::new(&lockfile).unwrap();
+    let app = graph.find("app", None).unwrap();
+    let log_0_4 = graph
+        .dependencies(graph.find("a", None).unwrap())
+        .iter()
+        .copied()
+        .find(|&id| graph.package(id).name == "log")
+        .unwrap();
+    let paths: Vec<Vec<String>> = graph
+        .paths(app, log_0_4)
+        .iter()
+        .map(|path| names(&graph, path))
+        .collect();
+    assert_eq!(
+        paths,
+        [
+            vec!["app 0.1.0", "a 0.1.0", "log 0.4.22"],
+            vec!["app 0.1.0", "log 0.3.9", "log 0.4.22"],
+        ]
+    );
+    assert_eq!(graph.paths(app, app), [vec![app]]);
+    assert!(graph.paths(log_0_4, app).is_empty());
+}
+
+#[test]
+fn rejects_dependencies_that_match_no_single_package() {
+    for dependency in ["log 0.4.22", "log 1.0.0", "log (registry+elsewhere)"] {
+        let lockfile = Lockfile::parse(&format!(
+            "version = 3\n\
+             [[package]]\nname = \"app\"\nversion = \"0.1.0\"\ndependencies = [\"{dependency}\"]\n\
+             [[package]]\nname = \"log\"\nversion = \"0.4.22\"\nsource = \"{REGISTRY}\"\n\
+             [[package]]\nname = \"log\"\nversion = \"0.4.22\"\nsource = \"{GIT}\"\n"
+        ))
+        .unwrap();
+        let error = Graph::new(&lockfile).unwrap_err().to_string();
+        assert!(
+            error.contains(&format!("dependency `{dependency}` of `app 0.1.0`")),
+            "{error}"
+        );
+    }
+}
@@ -0,0 +1,30 @@
+use std::path::Path;
+use std::process::Command;
+
+// `a` and `b` in `fixtures/links` need greet-sys 1.0.0 and 2.0.0, which both
+// declare `links = "greet"`. Any number of versions of `log` may coexist,
+// but only one package may link a given native library.
+#[test]
+fn two_versions_with_the_same_links_key_do_not_resolve() {
+    let manifest = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/links/Cargo.toml");
+    let output = Command::new(env!("CARGO"))
+        .args(["generate-lockfile", "--offline", "--manifest-path"])
+        .arg(&manifest)
+        .output()
+        .unwrap();
+    assert!(!output.status.success(), "{output:?}");
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    for expected in [
+        "error: failed to select a version for `greet-sys`.",
+        "package `greet-sys` links to the native library `greet`, but it conflicts with a \
+         previous package which links to `greet` as well:",
+        "package `greet-sys v1.0.0",
+        "Only one package in the dependency graph may specify the same links value.",
+    ] {
+        assert!(
+            stderr.contains(expected),
+            "missing {expected:?} in:\n{stderr}"
+        );
+    }
+}
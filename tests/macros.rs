@@ -0,0 +1,21 @@
+mod common;
+
+use common::{run, stdout};
+
+#[test]
+fn macros_logs_through_both_log_versions_from_the_root() {
+    let output = run(&["macros"]);
+    assert!(output.status.success(), "{output:?}");
+    let stdout = stdout(&output);
+    for message in [
+        "bare log! is log 0.3's, from #[macro_use]",
+        "log04::info! is log 0.4's",
+        "log03::info! is log 0.3's, but only thanks to #[macro_use]",
+        "[rust_incompatible_transitive_version_example::macros_04] bare info! is log 0.4's",
+    ] {
+        assert!(
+            stdout.contains(message),
+            "missing {message:?} in:\n{stdout}"
+        );
+    }
+}
@@ -0,0 +1,90 @@
+use rust_incompatible_transitive_version_example::duplicates;
+use rust_incompatible_transitive_version_example::graph::Graph;
+use rust_incompatible_transitive_version_example::npm;
+
+const FIXTURE: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/fixtures/npm/package-lock.json"
+);
+
+#[test]
+fn the_fixture_keeps_both_har_validators() {
+    let lockfile = npm::read(FIXTURE).unwrap();
+    let duplicates: Vec<String> = duplicates::find(&lockfile)
+        .iter()
+        .map(|d| {
+            let packages: Vec<String> = d.packages.iter().map(ToString::to_string).collect();
+            packages.join(", ")
+        })
+        .collect();
+    assert_eq!(
+        duplicates,
+        [
+            "ajv 4.11.8, ajv 6.12.6",
+            "har-schema 1.0.5, har-schema 2.0.0",
+            "har-validator 4.2.1, har-validator 5.1.5",
+        ]
+    );
+
+    // request loads the copy nested in its own node_modules.
+    let graph = Graph::new(&lockfile).unwrap();
+    let request = graph.find("request", None).unwrap();
+    let validators: Vec<String> = graph
+        .dependencies(request)
+        .iter()
+        .map(|&id| graph.package(id).to_string())
+        .collect();
+    assert_eq!(validators, ["har-validator 5.1.5"]);
+    let root = graph
+        .find("incompatible-dependencies-example", None)
+        .unwrap();
+    assert_eq!(graph.package(root).source, None);
+    assert_eq!(graph.roots(), [root]);
+}
+
+#[test]
+fn dependencies_resolve_to_the_nearest_copy() {
+    let lockfile = npm::parse(
+        r#"{
+          "lockfileVersion": 3,
+          "packages": {
+            "": { "name": "app", "dependencies": { "x": "1", "y": "1" } },
+            "node_modules/x": { "version": "1.0.0", "dependencies": { "z": "2" } },
+            "node_modules/x/node_modules/z": { "version": "2.0.0" },
+            "node_modules/y": { "version": "1.0.0", "dependencies": { "z": "1" },
+                                "optionalDependencies": { "fsevents": "2" } },
+            "node_modules/z": { "version": "1.0.0" }
+          }
+        }"#,
+    )
+    .unwrap();
+    let dependencies = |name: &str| {
+        lockfile
+            .packages
+            .iter()
+            .find(|p| p.name == name)
+            .unwrap()
+            .dependencies
+            .clone()
+    };
+    assert_eq!(
+        dependencies("x"),
+        ["z 2.0.0 (node_modules/x/node_modules/z)"]
+    );
+    assert_eq!(dependencies("y"), ["z 1.0.0 (node_modules/z)"]);
+    assert!(Graph::new(&lockfile).is_ok());
+}
+
+#[test]
+fn rejects_what_it_cannot_resolve() {
+    let v1 = npm::parse(r#"{ "lockfileVersion": 1, "dependencies": {} }"#).unwrap_err();
+    assert!(v1.to_string().contains("lockfile version 1"), "{v1}");
+    let missing = npm::parse(
+        r#"{ "lockfileVersion": 2, "packages": { "": { "dependencies": { "x": "1" } } } }"#,
+    )
+    .unwrap_err();
+    assert_eq!(
+        missing.to_string(),
+        "invalid package-lock.json: `x`, needed by the project, is not installed"
+    );
+}
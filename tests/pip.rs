@@ -0,0 +1,131 @@
+mod common;
+
+use common::{run, stdout};
+use rust_incompatible_transitive_version_example::duplicates;
+use rust_incompatible_transitive_version_example::explain;
+use rust_incompatible_transitive_version_example::graph::Graph;
+use rust_incompatible_transitive_version_example::pip;
+use rust_incompatible_transitive_version_example::resolve::{Pip, Strategy};
+
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/pip");
+
+#[test]
+fn translates_pep_440_specifiers() {
+    let cases = [
+        ("==2.0", "=2.0.0"),
+        ("==2.*", "=2.*"),
+        (">=0.15,<2.0", ">=0.15.0, <2.0.0"),
+        (">2.0", ">2.0.0"),
+        ("~=2.2", ">=2.2.0, =2.*"),
+        ("~=1.4.5", ">=1.4.5, =1.4.*"),
+        ("", "*"),
+    ];
+    for (specifier, req) in cases {
+        assert_eq!(
+            pip::specifier(specifier).unwrap(),
+            req.parse().unwrap(),
+            "{specifier}"
+        );
+    }
+    assert!(pip::specifier("!=1.0").is_err());
+    assert!(pip::specifier("~=1").is_err());
+}
+
+#[test]
+fn reads_requirements_files() {
+    let requirements = pip::requirements(
+        "# pinned for the demo\n\
+         Flask==2.0.0\n\
+         \n\
+         requests[socks] >= 2.0  # extras are ignored\n\
+         Zope.Interface; python_version < \"3.8\"\n",
+    )
+    .unwrap();
+    let names: Vec<String> = requirements.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        names,
+        ["flask =2.0.0", "requests >=2.0.0", "zope-interface *"]
+    );
+
+    let error = pip::requirements("flask\n-r other.txt\n").unwrap_err();
+    assert_eq!(error.to_string(), "line 2: `-r other.txt` is not supported");
+    assert_eq!(pip::normalize("Foo__Bar.baz"), "foo-bar-baz");
+}
+
+#[test]
+fn the_fixture_is_rejected_like_pip_rejects_it() {
+    let index = pip::read_index(format!("{FIXTURES}/index.toml")).unwrap();
+    let root = pip::read_requirements(format!("{FIXTURES}/requirements.txt")).unwrap();
+    let conflict = Pip.resolve(&index, &root).unwrap_err();
+    let report = explain::explain(&index, &root, &conflict).to_string();
+    assert!(
+        report.contains(
+            "The conflict is caused by:\n    \
+             flask 2.0.0 depends on werkzeug>=2.0.0\n    \
+             The user requested werkzeug==1.0.0\n"
+        ),
+        "{report}"
+    );
+    assert!(
+        report.contains("1. use flask 1.1.4 instead of flask 2.0.0\n"),
+        "{report}"
+    );
+
+    // Without the werkzeug pin, pip is happy with Flask 2.0's dependencies.
+    let resolution = Pip.resolve(&index, &root[..1]).unwrap();
+    let werkzeug = resolution.copies("werkzeug");
+    assert_eq!(werkzeug.len(), 1);
+    assert_eq!(werkzeug[0].version.to_string(), "2.0.0");
+}
+
+#[test]
+fn a_resolution_becomes_a_lockfile_the_reports_understand() {
+    let index = pip::read_index(format!("{FIXTURES}/index.toml")).unwrap();
+    let root = pip::requirements("flask==1.1.4\nwerkzeug==1.0.0\n").unwrap();
+    let lockfile = pip::lockfile(&Pip.resolve(&index, &root).unwrap());
+    assert!(duplicates::find(&lockfile).is_empty());
+
+    let graph = Graph::new(&lockfile).unwrap();
+    let roots = graph.roots();
+    assert_eq!(roots.len(), 1);
+    assert_eq!(graph.package(roots[0]).name, "root");
+    assert_eq!(graph.package(roots[0]).source, None);
+    let markupsafe = graph.find("markupsafe", None).unwrap();
+    let paths: Vec<Vec<String>> = graph
+        .paths(roots[0], markupsafe)
+        .iter()
+        .map(|path| {
+            path.iter()
+                .map(|&id| graph.package(id).to_string())
+                .collect()
+        })
+        .collect();
+    assert_eq!(
+        paths,
+        [[
+            "root 0.0.0",
+            "flask 1.1.4",
+            "jinja2 2.11.3",
+            "markupsafe 2.0.0"
+        ]]
+    );
+}
+
+#[test]
+fn ecosystems_reports_pip_rejecting_what_npm_and_cargo_accept() {
+    let output = run(&["ecosystems"]);
+    assert!(output.status.success(), "{output:?}");
+    let stdout = stdout(&output);
+    assert!(
+        stdout.contains("    log 0.3.8, 0.3.9, 0.4.22\n"),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("    har-validator 4.2.1, 5.1.5\n"),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("  rejected:\n    ERROR: Cannot install flask==2.0.0 and werkzeug==1.0.0"),
+        "{stdout}"
+    );
+}
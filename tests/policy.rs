@@ -0,0 +1,147 @@
+mod common;
+
+use std::path::Path;
+
+use common::{run, stdout};
+use rust_incompatible_transitive_version_example::graph::Graph;
+use rust_incompatible_transitive_version_example::lockfile::Lockfile;
+use rust_incompatible_transitive_version_example::manifest;
+use rust_incompatible_transitive_version_example::policy::{self, Level, Policy, Rule, Verdict};
+
+// `app` reaches log 0.3 through `b`, and through `x` once `x` is added.
+fn app_lockfile(with_x: bool) -> Lockfile {
+    let mut input = String::from(
+        "[[package]]\nname = \"app\"\nversion = \"0.1.0\"\n\
+         dependencies = [\"a\", \"b\", \"serde 1.0.1\", \"serde 1.0.2\"",
+    );
+    if with_x {
+        input.push_str(", \"x\"");
+    }
+    input.push_str(
+        "]\n\
+         [[package]]\nname = \"a\"\nversion = \"0.1.0\"\ndependencies = [\"log 0.4.22\"]\n\
+         [[package]]\nname = \"b\"\nversion = \"0.1.0\"\ndependencies = [\"log 0.3.9\"]\n\
+         [[package]]\nname = \"log\"\nversion = \"0.3.9\"\n\
+         [[package]]\nname = \"log\"\nversion = \"0.4.22\"\n\
+         [[package]]\nname = \"serde\"\nversion = \"1.0.1\"\n\
+         [[package]]\nname = \"serde\"\nversion = \"1.0.2\"\n",
+    );
+    if with_x {
+        input.push_str(
+            "[[package]]\nname = \"x\"\nversion = \"0.1.0\"\ndependencies = [\"log 0.3.9\"]\n",
+        );
+    }
+    Lockfile::parse(&input).unwrap()
+}
+
+#[test]
+fn parses_levels_and_via() {
+    let policy = Policy::parse(
+        "default = \"deny\"\n\
+         [crates.log]\nduplicates = \"allow\"\nvia = [\"b\"]\n",
+    )
+    .unwrap();
+    assert_eq!(policy.default, Level::Deny);
+    assert_eq!(
+        policy.rule("log"),
+        Rule {
+            level: Level::Allow,
+            via: vec!["b".into()]
+        }
+    );
+    assert_eq!(policy.rule("serde").level, Level::Deny);
+
+    assert!(Policy::parse("default = \"maybe\"").is_err());
+    assert!(Policy::parse("[crates.log]\nvia = [\"b\"]\n").is_err());
+    assert!(Policy::parse("[crates.log]\nduplicates = \"warn\"\nvia = [\"b\"]\n").is_err());
+}
+
+#[test]
+fn denies_duplicates_reached_outside_via() {
+    let policy = Policy::parse(
+        "[crates.log]\nduplicates = \"allow\"\nvia = [\"b\"]\n\
+         [crates.serde]\nduplicates = \"deny\"\n",
+    )
+    .unwrap();
+
+    let lockfile = app_lockfile(false);
+    let graph = Graph::new(&lockfile).unwrap();
+    let verdicts: Vec<(&str, Verdict)> = policy::check(&policy, &graph)
+        .into_iter()
+        .map(|f| (f.duplicate.name, f.verdict))
+        .collect();
+    assert_eq!(
+        verdicts,
+        [("log", Verdict::Allowed), ("serde", Verdict::Denied)]
+    );
+
+    // A second route to log 0.3.9 that skips `b` breaks the `via` rule.
+    let lockfile = app_lockfile(true);
+    let graph = Graph::new(&lockfile).unwrap();
+    let log = &policy::check(&policy, &graph)[0];
+    let Verdict::NotVia(packages) = &log.verdict else {
+        panic!("{log:?}");
+    };
+    let packages: Vec<String> = packages.iter().map(ToString::to_string).collect();
+    assert_eq!(packages, ["log 0.3.9", "log 0.4.22"]);
+    assert!(log.verdict.is_violation());
+}
+
+#[test]
+fn copies_asked_for_under_two_names_count_as_via() {
+    let policy = Policy::parse("[crates.log]\nduplicates = \"allow\"\nvia = [\"b\"]\n").unwrap();
+    let lockfile = Lockfile::parse(
+        "[[package]]\nname = \"app\"\nversion = \"0.1.0\"\n\
+         dependencies = [\"b\", \"bridge\", \"log 0.3.9\", \"log 0.4.22\"]\n\
+         [[package]]\nname = \"b\"\nversion = \"0.1.0\"\ndependencies = [\"log 0.3.9\"]\n\
+         [[package]]\nname = \"bridge\"\nversion = \"0.1.0\"\n\
+         dependencies = [\"log 0.3.9\", \"log 0.4.22\"]\n\
+         [[package]]\nname = \"log\"\nversion = \"0.3.9\"\n\
+         [[package]]\nname = \"log\"\nversion = \"0.4.22\"\n",
+    )
+    .unwrap();
+    let mut graph = Graph::new(&lockfile).unwrap();
+    let dual = "log03 = { package = \"log\", version = \"0.3.9\" }\n\
+                log04 = { package = \"log\", version = \"0.4.22\" }\n";
+    let app = manifest::parse(
+        Path::new("app/Cargo.toml"),
+        &format!(
+            "[package]\nname = \"app\"\nversion = \"0.1.0\"\n\n\
+             [dependencies]\nb = {{ path = \"../b\" }}\nbridge = {{ path = \"../bridge\" }}\n{dual}"
+        ),
+    )
+    .unwrap();
+    let bridge = manifest::parse(
+        Path::new("bridge/Cargo.toml"),
+        &format!("[package]\nname = \"bridge\"\nversion = \"0.1.0\"\n\n[dependencies]\n{dual}"),
+    )
+    .unwrap();
+    let keys: Vec<&str> = app
+        .dual_dependencies()
+        .iter()
+        .map(|d| d.key.as_str())
+        .collect();
+    assert_eq!(keys, ["log03", "log04"]);
+
+    // Without the manifests, both copies hang off `app` and `bridge` directly.
+    assert!(policy::check(&policy, &graph)[0].verdict.is_violation());
+    // The root's pair alone isn't enough: `bridge` has one too.
+    let manifests = [app, bridge];
+    graph.set_intentional(manifest::dual_edges(&graph, &manifests[..1]));
+    assert!(policy::check(&policy, &graph)[0].verdict.is_violation());
+    graph.set_intentional(manifest::dual_edges(&graph, &manifests));
+    let (from, to) = (graph.find("bridge", None), graph.find("log", Some("0.3.9")));
+    assert!(graph.is_intentional(from.unwrap(), to.unwrap()));
+    assert_eq!(policy::check(&policy, &graph)[0].verdict, Verdict::Allowed);
+}
+
+#[test]
+fn check_accepts_the_repo_policy() {
+    let output = run(&["check"]);
+    assert!(output.status.success(), "{output:?}");
+    let stdout = stdout(&output);
+    assert!(
+        stdout.starts_with("allowed  log 0.3.8, 0.3.9, 0.4.22 (via b, c)\n"),
+        "{stdout}"
+    );
+}
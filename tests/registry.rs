@@ -0,0 +1,166 @@
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use flate2::read::GzDecoder;
+use rust_incompatible_transitive_version_example::cargo;
+use rust_incompatible_transitive_version_example::registry;
+use rust_incompatible_transitive_version_example::semver::Version;
+use sha2::{Digest, Sha256};
+
+// The fixture releases, published once for all the tests.
+fn fixtures() -> &'static Path {
+    static PUBLISHED: OnceLock<PathBuf> = OnceLock::new();
+    PUBLISHED.get_or_init(|| {
+        let out = scratch("local-registry");
+        let _ = fs::remove_dir_all(&out);
+        registry::publish(&sources(), &out).unwrap();
+        out
+    })
+}
+
+fn sources() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join(registry::DIR)
+}
+
+fn scratch(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_TARGET_TMPDIR"))
+        .join("registry")
+        .join(name)
+}
+
+fn hex(digest: &[u8]) -> String {
+    digest.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn locked_log(consumers: &[(&str, &str)], name: &str) -> Vec<Version> {
+    let dir = scratch(name);
+    registry::write_consumers(&dir, consumers).unwrap();
+    let lockfile = registry::resolve(fixtures(), &dir).unwrap();
+    lockfile
+        .packages
+        .into_iter()
+        .filter(|p| p.name == "log")
+        .map(|p| p.version)
+        .collect()
+}
+
+fn resolution_error(consumers: &[(&str, &str)], name: &str) -> String {
+    let dir = scratch(name);
+    registry::write_consumers(&dir, consumers).unwrap();
+    match registry::resolve(fixtures(), &dir) {
+        Err(registry::Error::Cargo(cargo::Error::Failed { stderr, .. })) => stderr,
+        other => panic!("expected Cargo to fail, got {other:?}"),
+    }
+}
+
+#[test]
+fn index_paths_follow_the_name_length() {
+    for (name, path) in [
+        ("a", "1/a"),
+        ("cc", "2/cc"),
+        ("log", "3/l/log"),
+        ("Serde", "se/rd/serde"),
+    ] {
+        assert_eq!(registry::index_path(name), Path::new(path));
+    }
+}
+
+#[test]
+fn publishing_packs_every_release_and_indexes_it() {
+    let out = scratch("published");
+    let _ = fs::remove_dir_all(&out);
+    let releases = registry::publish(&sources(), &out).unwrap();
+
+    let versions: Vec<(String, bool)> = releases
+        .iter()
+        .map(|r| (r.version.to_string(), r.yanked))
+        .collect();
+    assert_eq!(
+        versions,
+        [
+            ("0.3.8".into(), false),
+            ("0.3.9".into(), false),
+            ("0.4.10".into(), false),
+            ("0.4.22".into(), false),
+            ("0.4.23".into(), true),
+        ]
+    );
+    let index = fs::read_to_string(out.join("index/3/l/log")).unwrap();
+    assert_eq!(index.lines().count(), releases.len());
+
+    for release in &releases {
+        let prefix = format!("{}-{}", release.name, release.version);
+        let data = fs::read(out.join(format!("{prefix}.crate"))).unwrap();
+        let checksum = hex(&Sha256::digest(&data));
+        assert_eq!(checksum, release.checksum);
+        assert!(index.contains(&format!("\"cksum\":\"{checksum}\"")));
+
+        let mut manifest = String::new();
+        let mut archive = tar::Archive::new(GzDecoder::new(&data[..]));
+        let mut paths = Vec::new();
+        for entry in archive.entries().unwrap() {
+            let mut entry = entry.unwrap();
+            let path = entry.path().unwrap().to_string_lossy().into_owned();
+            if path == format!("{prefix}/Cargo.toml") {
+                entry.read_to_string(&mut manifest).unwrap();
+            }
+            paths.push(path);
+        }
+        assert!(paths.iter().all(|p| p.starts_with(&format!("{prefix}/"))));
+        assert_eq!(
+            manifest,
+            fs::read_to_string(sources().join(&prefix).join("Cargo.toml")).unwrap()
+        );
+
+        // Packing is reproducible, so checksums don't change between runs.
+        assert_eq!(
+            registry::pack(&sources().join(&prefix), &release.name, &release.version).unwrap(),
+            data
+        );
+    }
+}
+
+#[test]
+fn compatible_requirements_share_the_highest_version() {
+    assert_eq!(
+        locked_log(&[("a", "0.4.22"), ("b", "0.4.10")], "shared"),
+        ["0.4.22".parse::<Version>().unwrap()]
+    );
+}
+
+#[test]
+fn a_lower_requirement_alone_still_gets_the_newest_unyanked_release() {
+    assert_eq!(
+        locked_log(&[("b", "0.4.10")], "alone"),
+        ["0.4.22".parse::<Version>().unwrap()]
+    );
+}
+
+#[test]
+fn an_exact_requirement_cannot_share_with_a_compatible_one() {
+    let stderr = resolution_error(&[("a", "0.4.22"), ("b", "=0.4.10")], "exact");
+    assert!(stderr.contains("failed to select a version for `log`"));
+    assert!(stderr.contains("previously selected package `log v0.4.22`"));
+}
+
+#[test]
+fn a_yanked_release_cannot_be_newly_locked() {
+    let stderr = resolution_error(&[("b", "=0.4.23")], "yanked");
+    assert!(stderr.contains("version 0.4.23 is yanked"));
+}
+
+#[test]
+fn incompatible_versions_are_both_built() {
+    let dir = scratch("split");
+    registry::write_consumers(&dir, &[("a", "0.4.22"), ("b", "0.3")]).unwrap();
+    let versions = registry::run(fixtures(), &dir).unwrap();
+    assert_eq!(
+        versions,
+        [
+            ("a".to_string(), "0.4.22".parse().unwrap()),
+            ("b".to_string(), "0.3.9".parse().unwrap()),
+        ]
+    );
+}
@@ -0,0 +1,210 @@
+mod common;
+
+use common::{run, stdout};
+use rust_incompatible_transitive_version_example::resolve::{
+    Cargo, Dependency, Index, Npm, Pip, Resolution, Strategy,
+};
+
+fn dependencies(deps: &[(&str, &str)]) -> Vec<Dependency> {
+    deps.iter()
+        .map(|(name, req)| Dependency::new(*name, req.parse().unwrap()))
+        .collect()
+}
+
+// Name, version and dependencies.
+type Release<'a> = (&'a str, &'a str, &'a [(&'a str, &'a str)]);
+
+fn index(releases: &[Release]) -> Index {
+    let mut index = Index::new();
+    for (name, version, deps) in releases {
+        index.add(name, version.parse().unwrap(), dependencies(deps));
+    }
+    index
+}
+
+// `a` wants log 0.4, `b` wants log 0.3, and log 0.3.9 forwards to log 0.4.
+fn workspace() -> Index {
+    index(&[
+        ("a", "0.1.0", &[("log", "0.4.22")]),
+        ("b", "0.1.0", &[("log", "0.3.9")]),
+        ("log", "0.3.8", &[]),
+        ("log", "0.3.9", &[("log", "0.4")]),
+        ("log", "0.4.0", &[]),
+        ("log", "0.4.22", &[]),
+    ])
+}
+
+fn versions(resolution: &Resolution, name: &str) -> Vec<String> {
+    resolution
+        .copies(name)
+        .iter()
+        .map(|p| p.version.to_string())
+        .collect()
+}
+
+fn paths(resolution: &Resolution) -> Vec<String> {
+    let mut paths: Vec<String> = resolution
+        .packages
+        .iter()
+        .map(|p| format!("{} {}", p.path(), p.version))
+        .collect();
+    paths.sort();
+    paths
+}
+
+#[test]
+fn cargo_keeps_one_log_per_compatible_range() {
+    let root = dependencies(&[("a", "0.1"), ("b", "0.1")]);
+    let resolution = Cargo.resolve(&workspace(), &root).unwrap();
+    assert_eq!(versions(&resolution, "log"), ["0.3.9", "0.4.22"]);
+
+    // log 0.3.9's own dependency on log 0.4 reuses a's copy.
+    let log_0_3 = resolution
+        .packages
+        .iter()
+        .position(|p| p.version.to_string() == "0.3.9")
+        .unwrap();
+    let forwarded = &resolution.packages[resolution.packages[log_0_3].dependencies[0]];
+    assert_eq!(forwarded.version.to_string(), "0.4.22");
+    assert_eq!(resolution.root.len(), 2);
+}
+
+#[test]
+fn cargo_unifies_compatible_requirements_on_the_newest_release() {
+    let index = index(&[
+        ("a", "0.1.0", &[("log", "0.4.0")]),
+        ("b", "0.1.0", &[("log", "0.4")]),
+        ("log", "0.4.0", &[]),
+        ("log", "0.4.22", &[]),
+    ]);
+    let root = dependencies(&[("a", "0.1"), ("b", "0.1")]);
+    let resolution = Cargo.resolve(&index, &root).unwrap();
+    assert_eq!(versions(&resolution, "log"), ["0.4.22"]);
+}
+
+#[test]
+fn cargo_backtracks_when_an_exact_requirement_comes_later() {
+    let index = index(&[
+        ("a", "0.1.0", &[("log", "0.4")]),
+        ("b", "0.1.0", &[("log", "=0.4.0")]),
+        ("log", "0.4.0", &[]),
+        ("log", "0.4.22", &[]),
+    ]);
+    let root = dependencies(&[("a", "0.1"), ("b", "0.1")]);
+    let resolution = Cargo.resolve(&index, &root).unwrap();
+    assert_eq!(versions(&resolution, "log"), ["0.4.0"]);
+}
+
+#[test]
+fn pip_refuses_two_versions_of_log() {
+    let root = dependencies(&[("a", "0.1"), ("b", "0.1")]);
+    let conflict = Pip.resolve(&workspace(), &root).unwrap_err();
+    assert_eq!(conflict.wanted.dependency.name, "log");
+    assert_eq!(
+        conflict.wanted.by,
+        Some(("b".to_string(), "0.1.0".parse().unwrap()))
+    );
+    assert_eq!(conflict.chosen.len(), 1);
+    assert_eq!(conflict.chosen[0].version.to_string(), "0.4.22");
+    assert_eq!(
+        conflict.to_string(),
+        "b 0.1.0 requires log ^0.3.9, but log 0.4.22 was already chosen because \
+         a 0.1.0 requires log ^0.4.22"
+    );
+}
+
+#[test]
+fn pip_backtracks_to_an_older_release_that_fits() {
+    let index = index(&[
+        ("flask", "1.1.4", &[("werkzeug", ">=0.15, <2.0")]),
+        ("flask", "2.0.0", &[("werkzeug", ">=2.0")]),
+        ("werkzeug", "1.0.0", &[]),
+        ("werkzeug", "2.0.0", &[]),
+    ]);
+    let root = dependencies(&[("flask", "*"), ("werkzeug", "=1.0.0")]);
+    let resolution = Pip.resolve(&index, &root).unwrap();
+    assert_eq!(versions(&resolution, "flask"), ["1.1.4"]);
+    assert_eq!(versions(&resolution, "werkzeug"), ["1.0.0"]);
+
+    let root = dependencies(&[("flask", "=2.0.0"), ("werkzeug", "=1.0.0")]);
+    let conflict = Pip.resolve(&index, &root).unwrap_err();
+    assert_eq!(
+        conflict.to_string(),
+        "flask 2.0.0 requires werkzeug >=2.0, but werkzeug 1.0.0 was already chosen \
+         because the root requires werkzeug =1.0.0"
+    );
+}
+
+#[test]
+fn npm_nests_the_copies_that_do_not_fit_above() {
+    let root = dependencies(&[("a", "0.1"), ("b", "0.1")]);
+    let resolution = Npm.resolve(&workspace(), &root).unwrap();
+    assert_eq!(
+        paths(&resolution),
+        [
+            "node_modules/a 0.1.0",
+            "node_modules/b 0.1.0",
+            "node_modules/b/node_modules/log 0.3.9",
+            // log 0.3.9 finds itself first, so its log 0.4 is nested again.
+            "node_modules/b/node_modules/log/node_modules/log 0.4.22",
+            "node_modules/log 0.4.22",
+        ]
+    );
+}
+
+#[test]
+fn npm_shares_a_copy_that_matches() {
+    let index = index(&[
+        ("har-validator", "4.2.1", &[]),
+        ("har-validator", "5.1.5", &[]),
+        ("request", "2.88.2", &[("har-validator", "~5.1.3")]),
+        ("tool", "1.0.0", &[("har-validator", "4")]),
+    ]);
+    let root = dependencies(&[
+        ("har-validator", "^4.2.1"),
+        ("request", "^2.88.2"),
+        ("tool", "1"),
+    ]);
+    let resolution = Npm.resolve(&index, &root).unwrap();
+    assert_eq!(
+        paths(&resolution),
+        [
+            "node_modules/har-validator 4.2.1",
+            "node_modules/request 2.88.2",
+            "node_modules/request/node_modules/har-validator 5.1.5",
+            "node_modules/tool 1.0.0",
+        ]
+    );
+}
+
+#[test]
+fn every_strategy_reports_a_requirement_nothing_matches() {
+    let index = index(&[("a", "0.1.0", &[("log", "0.5")]), ("log", "0.4.22", &[])]);
+    let root = dependencies(&[("a", "0.1")]);
+    let strategies: [&dyn Strategy; 3] = [&Cargo, &Pip, &Npm];
+    for strategy in strategies {
+        let conflict = strategy.resolve(&index, &root).unwrap_err();
+        assert!(conflict.chosen.is_empty(), "{}", strategy.name());
+        assert_eq!(
+            conflict.to_string(),
+            "a 0.1.0 requires log ^0.5, but no release matches"
+        );
+    }
+}
+
+#[test]
+fn resolve_contrasts_cargo_pip_and_npm() {
+    let output = run(&["resolve"]);
+    assert!(output.status.success(), "{output:?}");
+    let stdout = stdout(&output);
+    assert!(
+        stdout.contains("  2 copies of log: 0.3.9, 0.4.22\n"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("pip\n  no solution: "), "{stdout}");
+    assert!(
+        stdout.contains("  node_modules/b/node_modules/log 0.3.9\n"),
+        "{stdout}"
+    );
+    assert!(!run(&["resolve", "pip"]).status.success());
+}
@@ -0,0 +1,137 @@
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, UNIX_EPOCH};
+
+use rust_incompatible_transitive_version_example::graph::Graph;
+use rust_incompatible_transitive_version_example::lockfile::Lockfile;
+use rust_incompatible_transitive_version_example::manifest;
+use rust_incompatible_transitive_version_example::sbom::{self, Sbom};
+
+const REGISTRY: &str = "registry+https://github.com/rust-lang/crates.io-index";
+
+// `app` needs log 0.4 directly and log 0.3 through `b`, and has `trybuild`
+// as a dev-dependency.
+fn lockfile() -> Lockfile {
+    Lockfile::parse(&format!(
+        "[[package]]\nname = \"app\"\nversion = \"0.1.0\"\n\
+         dependencies = [\"b\", \"log 0.4.22\", \"trybuild\"]\n\
+         [[package]]\nname = \"b\"\nversion = \"0.1.0\"\n\
+         source = \"git+https://example.com/b.git?branch=main#0123abc\"\n\
+         dependencies = [\"log 0.3.9\"]\n\
+         [[package]]\nname = \"log\"\nversion = \"0.3.9\"\nsource = \"{REGISTRY}\"\n\
+         checksum = \"e19e\"\ndependencies = [\"log 0.4.22\"]\n\
+         [[package]]\nname = \"log\"\nversion = \"0.4.22\"\nsource = \"{REGISTRY}\"\n\
+         checksum = \"a7a7\"\n\
+         [[package]]\nname = \"trybuild\"\nversion = \"1.0.122\"\nsource = \"{REGISTRY}\"\n"
+    ))
+    .unwrap()
+}
+
+fn manifests() -> Vec<manifest::Manifest> {
+    let root = Path::new(env!("CARGO_TARGET_TMPDIR")).join("sbom");
+    fs::create_dir_all(&root).unwrap();
+    fs::write(
+        root.join("Cargo.toml"),
+        "[package]\nname = \"app\"\nversion = \"0.1.0\"\n\
+         [dependencies]\nlog = \"0.4\"\nb = { git = \"https://example.com/b.git\" }\n\
+         [dev-dependencies]\ntrybuild = \"1\"\n",
+    )
+    .unwrap();
+    manifest::workspace(root.join("Cargo.toml")).unwrap()
+}
+
+#[test]
+fn purls_carry_non_crates_io_sources() {
+    let lockfile = lockfile();
+    let purls: Vec<Option<String>> = lockfile.packages.iter().map(sbom::purl).collect();
+    assert_eq!(purls[0], None);
+    assert_eq!(
+        purls[1].as_deref(),
+        Some("pkg:cargo/b@0.1.0?vcs_url=git%2Bhttps%3A%2F%2Fexample.com%2Fb.git%3Fbranch%3Dmain%230123abc")
+    );
+    assert_eq!(purls[2].as_deref(), Some("pkg:cargo/log@0.3.9"));
+}
+
+#[test]
+fn cyclonedx_lists_each_log_version_with_its_checksum() {
+    let lockfile = lockfile();
+    let graph = Graph::new(&lockfile).unwrap();
+    let sbom = Sbom::new(&graph, 0, &manifests());
+    let names: Vec<String> = sbom
+        .components
+        .iter()
+        .map(|c| c.package.to_string())
+        .collect();
+    assert_eq!(names, ["app 0.1.0", "b 0.1.0", "log 0.3.9", "log 0.4.22"]);
+
+    // Round-trip through the writer to check it emits valid JSON.
+    let document =
+        serde_json::from_str::<serde_json::Value>(&sbom::cyclonedx(&sbom).to_string()).unwrap();
+    let document = document.as_object().unwrap();
+    assert_eq!(document["bomFormat"].as_str(), Some("CycloneDX"));
+    let hashes: Vec<(&str, &str)> = document["components"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|c| {
+            let c = c.as_object().unwrap();
+            let hash = c.get("hashes")?.as_array()?[0].as_object()?;
+            Some((c["bom-ref"].as_str()?, hash["content"].as_str()?))
+        })
+        .collect();
+    assert_eq!(
+        hashes,
+        [
+            ("pkg:cargo/log@0.3.9", "e19e"),
+            ("pkg:cargo/log@0.4.22", "a7a7")
+        ]
+    );
+    let log_0_3 = document["dependencies"].as_array().unwrap()[2]
+        .as_object()
+        .unwrap();
+    assert_eq!(log_0_3["ref"].as_str(), Some("pkg:cargo/log@0.3.9"));
+    assert_eq!(
+        *log_0_3["dependsOn"].as_array().unwrap(),
+        [serde_json::Value::String("pkg:cargo/log@0.4.22".into())]
+    );
+}
+
+#[test]
+fn spdx_records_packages_and_relationships() {
+    let lockfile = lockfile();
+    let graph = Graph::new(&lockfile).unwrap();
+    let sbom = Sbom::new(&graph, 0, &manifests());
+    let created = UNIX_EPOCH + Duration::from_secs(1_723_935_722);
+    let spdx = sbom::spdx(&sbom, created);
+    assert!(spdx.starts_with("SPDXVersion: SPDX-2.3\n"), "{spdx}");
+    assert!(spdx.contains("Created: 2024-08-17T23:02:02Z\n"), "{spdx}");
+    assert!(
+        spdx.contains(
+            "PackageName: log\n\
+             SPDXID: SPDXRef-log-0.3.9\n\
+             PackageVersion: 0.3.9\n\
+             PackageDownloadLocation: https://crates.io/api/v1/crates/log/0.3.9/download\n\
+             FilesAnalyzed: false\n\
+             PackageChecksum: SHA256: e19e\n"
+        ),
+        "{spdx}"
+    );
+    assert!(
+        spdx.contains("PackageDownloadLocation: git+https://example.com/b.git@0123abc\n"),
+        "{spdx}"
+    );
+    let relationships: Vec<&str> = spdx
+        .lines()
+        .filter_map(|l| l.strip_prefix("Relationship: "))
+        .collect();
+    assert_eq!(
+        relationships,
+        [
+            "SPDXRef-DOCUMENT DESCRIBES SPDXRef-app-0.1.0",
+            "SPDXRef-app-0.1.0 DEPENDS_ON SPDXRef-b-0.1.0",
+            "SPDXRef-app-0.1.0 DEPENDS_ON SPDXRef-log-0.4.22",
+            "SPDXRef-b-0.1.0 DEPENDS_ON SPDXRef-log-0.3.9",
+            "SPDXRef-log-0.3.9 DEPENDS_ON SPDXRef-log-0.4.22",
+        ]
+    );
+}
@@ -0,0 +1,133 @@
+mod common;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use common::{run, stdout};
+use rust_incompatible_transitive_version_example::scenario::{self, Report, Scenario};
+use rust_incompatible_transitive_version_example::semver::Version;
+
+fn versions(versions: &[&str]) -> Vec<Version> {
+    versions.iter().map(|v| v.parse().unwrap()).collect()
+}
+
+fn scratch(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_TARGET_TMPDIR"))
+        .join("scenario")
+        .join(name)
+}
+
+fn copies(outcome: &scenario::Outcome) -> Vec<(&str, String, usize)> {
+    outcome
+        .reports
+        .iter()
+        .map(
+            |Report {
+                 leaf,
+                 version,
+                 copy,
+             }| (leaf.as_str(), version.to_string(), *copy),
+        )
+        .collect()
+}
+
+#[test]
+fn writes_one_shared_crate_per_distinct_version() {
+    let dir = scratch("written");
+    let scenario = Scenario::deep(2, &versions(&["2.0.0", "0.1.0", "2.0.0"]));
+    scenario.write(&dir).unwrap();
+
+    assert_eq!(
+        scenario.versions(),
+        [&"0.1.0".parse().unwrap(), &"2.0.0".parse().unwrap()]
+    );
+    for version in ["0.1.0", "2.0.0"] {
+        let manifest = fs::read_to_string(dir.join("shared").join(version).join("Cargo.toml"));
+        assert!(manifest
+            .unwrap()
+            .contains(&format!("version = \"{version}\"")));
+    }
+    let leaf = fs::read_to_string(dir.join("leaf1/Cargo.toml")).unwrap();
+    assert!(leaf.contains("next = { package = \"leaf1-1\", path = \"../leaf1-1\" }"));
+    let end = fs::read_to_string(dir.join("leaf1-1/Cargo.toml")).unwrap();
+    assert!(end.contains("next = { package = \"shared\", path = \"../shared/0.1.0\" }"));
+    let root = fs::read_to_string(dir.join("Cargo.toml")).unwrap();
+    assert!(root.contains("leaf2 = { path = \"leaf2\" }"));
+    assert!(root.contains("exclude = [\"shared\"]"));
+}
+
+#[test]
+fn five_versions_are_five_copies() {
+    let dir = scratch("five");
+    let scenario = Scenario::fan(&versions(&["0.1.0", "0.2.0", "0.3.0", "1.0.0", "2.0.0"]));
+    scenario.write(&dir).unwrap();
+    let outcome = scenario::build(&dir).unwrap();
+
+    assert_eq!(
+        outcome.locked,
+        versions(&["0.1.0", "0.2.0", "0.3.0", "1.0.0", "2.0.0"])
+    );
+    assert_eq!(
+        copies(&outcome),
+        [
+            ("leaf0", "0.1.0".into(), 1),
+            ("leaf1", "0.2.0".into(), 2),
+            ("leaf2", "0.3.0".into(), 3),
+            ("leaf3", "1.0.0".into(), 4),
+            ("leaf4", "2.0.0".into(), 5),
+        ]
+    );
+}
+
+#[test]
+fn a_diamond_on_one_version_shares_one_copy() {
+    let dir = scratch("diamond");
+    let scenario = Scenario::fan(&versions(&["1.0.0", "1.0.0"]));
+    scenario.write(&dir).unwrap();
+    let outcome = scenario::build(&dir).unwrap();
+
+    assert_eq!(outcome.locked, versions(&["1.0.0"]));
+    assert_eq!(outcome.copies(), 1);
+}
+
+#[test]
+fn deep_chains_reach_their_own_copies() {
+    let dir = scratch("deep");
+    let scenario = Scenario::deep(3, &versions(&["0.1.0", "0.2.0", "0.1.0"]));
+    scenario.write(&dir).unwrap();
+    let outcome = scenario::build(&dir).unwrap();
+
+    assert_eq!(outcome.locked, versions(&["0.1.0", "0.2.0"]));
+    assert_eq!(
+        copies(&outcome),
+        [
+            ("leaf0", "0.1.0".into(), 1),
+            ("leaf1", "0.2.0".into(), 2),
+            ("leaf2", "0.1.0".into(), 1),
+        ]
+    );
+}
+
+// Path dependencies are never unified by version, only by path, so even
+// two SemVer-compatible versions stay two copies.
+#[test]
+fn compatible_path_copies_are_not_unified() {
+    let dir = scratch("compatible");
+    let scenario = Scenario::fan(&versions(&["1.0.0", "1.1.0"]));
+    scenario.write(&dir).unwrap();
+    let outcome = scenario::build(&dir).unwrap();
+
+    assert_eq!(outcome.locked, versions(&["1.0.0", "1.1.0"]));
+    assert_eq!(outcome.copies(), 2);
+}
+
+#[test]
+fn the_subcommand_reports_one_copy_for_a_diamond() {
+    let dir = scratch("subcommand");
+    let output = run(&["scenario", "--dir", dir.to_str().unwrap(), "1.0.0", "1.0.0"]);
+    assert!(output.status.success());
+    let stdout = stdout(&output);
+    assert!(stdout.contains("leaf0 reached shared 1.0.0 (copy 1)"));
+    assert!(stdout.contains("leaf1 reached shared 1.0.0 (copy 1)"));
+    assert!(stdout.contains("Cargo.lock has 1 shared: 1.0.0; 1 copy at run time"));
+}
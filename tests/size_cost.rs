@@ -0,0 +1,114 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use rust_incompatible_transitive_version_example::cost;
+use rust_incompatible_transitive_version_example::semver::Version;
+
+#[test]
+fn rlibs_hold_object_files() {
+    let deps = Path::new(env!(
+        "CARGO_BIN_EXE_rust-incompatible-transitive-version-example"
+    ))
+    .parent()
+    .unwrap()
+    .join("deps");
+    let rlib = fs::read_dir(deps)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| {
+            let name = path.file_name().unwrap().to_str().unwrap();
+            name.starts_with("liblog-") && name.ends_with(".rlib")
+        })
+        .unwrap();
+    assert!(cost::codegen_units(&rlib).unwrap() > 0);
+}
+
+// Every file of the workspace, relative to `dir`, without build output.
+fn files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut stack = vec![PathBuf::new()];
+    while let Some(relative) = stack.pop() {
+        for entry in fs::read_dir(dir.join(&relative)).unwrap() {
+            let entry = entry.unwrap();
+            let path = relative.join(entry.file_name());
+            if entry.file_name() == "target" || entry.file_name() == ".git" {
+                continue;
+            }
+            if entry.file_type().unwrap().is_dir() {
+                stack.push(path);
+            } else {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+#[test]
+fn only_b_and_its_callers_change_in_the_unified_copy() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let scratch = Path::new(env!("CARGO_TARGET_TMPDIR")).join("size-cost-copies");
+    cost::copy_workspace(root, &scratch.join("split")).unwrap();
+    cost::copy_workspace(root, &scratch.join("unify")).unwrap();
+    cost::unify(&scratch.join("unify")).unwrap();
+
+    let files = files(root);
+    let edited = ["b/Cargo.toml", "b/src/lib.rs", "src/main.rs"].map(Path::new);
+    for dir in ["split", "unify"] {
+        let dir = scratch.join(dir);
+        assert_eq!(self::files(&dir), files);
+        for file in &files {
+            let (copy, original) = (
+                fs::read(dir.join(file)).unwrap(),
+                fs::read(root.join(file)).unwrap(),
+            );
+            if dir.ends_with("split") || !edited.contains(&file.as_path()) {
+                assert!(copy == original, "{}", file.display());
+            } else {
+                assert!(copy != original, "{}", file.display());
+            }
+        }
+    }
+    let unify = scratch.join("unify");
+    let manifest = fs::read_to_string(unify.join("b/Cargo.toml")).unwrap();
+    assert!(manifest.contains("\nlog = \"0.4\"\n"), "{manifest}");
+    let lib = fs::read_to_string(unify.join("b/src/lib.rs")).unwrap();
+    assert!(
+        !lib.contains("LogLevel") && !lib.contains("max_log_level"),
+        "{lib}"
+    );
+}
+
+#[test]
+fn moving_b_to_log_0_4_changes_only_b() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    // Both builds share one target directory, so the second one finds the
+    // first one's rlibs next to its own.
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("size-cost");
+    cost::copy_workspace(root, &dir).unwrap();
+    let split = cost::build(&dir, env!("CARGO_PKG_NAME")).unwrap();
+    cost::unify(&dir).unwrap();
+    let unified = cost::build(&dir, env!("CARGO_PKG_NAME")).unwrap();
+
+    // `bridge` and the binary keep log 0.3.9, and `c` keeps log 0.3.8.
+    for version in ["0.3.8", "0.3.9", "0.4.22"] {
+        let version: Version = version.parse().unwrap();
+        assert_eq!(
+            split.find("log", Some(&version)),
+            unified.find("log", Some(&version)),
+            "{version}"
+        );
+    }
+    for build in [&split, &unified] {
+        let b: Vec<_> = build.crates.iter().filter(|c| c.name == "b").collect();
+        assert_eq!(b.len(), 1, "{b:?}");
+    }
+    let changed: Vec<&str> = split
+        .crates
+        .iter()
+        .filter(|&c| !unified.crates.contains(c))
+        .map(|c| c.name.as_str())
+        .collect();
+    assert_eq!(changed, ["b"]);
+}
@@ -0,0 +1,75 @@
+use std::path::Path;
+
+use rust_incompatible_transitive_version_example::symbols::{self, demangle};
+
+#[test]
+fn demangles_v0_symbols_with_their_crate() {
+    let logger = demangle("_RNvCs7o63nRAkSPi_3log6LOGGER").unwrap();
+    assert_eq!(logger.path, "log::LOGGER");
+    assert_eq!(logger.krate.name, "log");
+    assert!(logger.krate.disambiguator.is_some());
+
+    let demangled = demangle(
+        "_RINvMNtNtCsgEmfK2I1SDS_4core3fmt2rtNtB3_8Argument9new_debug\
+         NtCs7o63nRAkSPi_3log5LevelECsln7sJ9EVso8_44rust_incompatible_transitive_version_example",
+    )
+    .unwrap();
+    assert_eq!(
+        demangled.path,
+        "<core::fmt::rt::Argument>::new_debug::<log::Level>"
+    );
+    assert_eq!(demangled.krate.name, "core");
+    assert!(demangled.krate.disambiguator.is_some());
+
+    // A closure in `log::set_boxed_logger`: the same crate, the same hash.
+    let closure = demangle("_RNCNvCs7o63nRAkSPi_3log16set_boxed_logger0B3_").unwrap();
+    assert_eq!(closure.path, "log::set_boxed_logger::{closure#0}");
+    assert_eq!(closure.krate, logger.krate);
+}
+
+#[test]
+fn demangles_legacy_symbols_without_a_disambiguator() {
+    let demangled = demangle("_ZN4core3fmt9Formatter3pad17h0123456789abcdefE").unwrap();
+    assert_eq!(demangled.path, "core::fmt::Formatter::pad");
+    assert_eq!(demangled.krate.name, "core");
+    assert_eq!(demangled.krate.disambiguator, None);
+
+    let demangled =
+        demangle("_ZN49_$LT$log..Level$u20$as$u20$core..fmt..Display$GT$3fmt17h0123456789abcdefE")
+            .unwrap();
+    assert_eq!(demangled.path, "<log::Level as core::fmt::Display>::fmt");
+    assert_eq!(demangled.krate.name, "log");
+}
+
+#[test]
+fn ignores_symbols_that_name_no_rust_crate() {
+    assert_eq!(demangle("memcpy"), None);
+    assert_eq!(demangle("_ZN"), None);
+    // `<str>::trim_start_matches::<&str>`
+    assert_eq!(
+        demangle(
+            "_RINvMNtCsgEmfK2I1SDS_4core3stre18trim_start_matchesReECsgY6Mt91CT9J_14rustc_demangle"
+        ),
+        None
+    );
+}
+
+#[test]
+fn the_example_binary_links_three_log_crates() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let target = Path::new(env!("CARGO_TARGET_TMPDIR")).join("symbols");
+    let binary = symbols::build(root, env!("CARGO_PKG_NAME"), &target).unwrap();
+    let crates = symbols::by_crate(&symbols::read(&binary).unwrap());
+    let versions = symbols::versions(binary.with_file_name("deps"), root).unwrap();
+
+    let mut logs = Vec::new();
+    for log in crates.iter().filter(|c| c.krate.name == "log") {
+        assert!(log.krate.disambiguator.is_some(), "{log:#?}");
+        assert!(log.code_bytes > 0, "{log:#?}");
+        assert!(log.symbols.iter().any(|s| s == "log::LOGGER"), "{log:#?}");
+        logs.push(versions[&log.krate].to_string());
+    }
+    logs.sort();
+    // log 0.3.8 (`c`), log 0.3.9 (`b`) and log 0.4.22 (`a` and everyone else)
+    assert_eq!(logs, ["0.3.8", "0.3.9", "0.4.22"]);
+}
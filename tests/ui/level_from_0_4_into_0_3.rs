@@ -0,0 +1,4 @@
+// `a` hands out a log 0.4 `Level`, `b` only accepts a log 0.3 `LogLevel`.
+fn main() {
+    b::log_at(a::level(), "from a to b");
+}
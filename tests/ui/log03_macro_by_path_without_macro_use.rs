@@ -0,0 +1,5 @@
+// log 0.3's `info!` expands to a bare `log!`, which is only in scope after
+// `#[macro_use] extern crate log03`.
+fn main() {
+    log03::info!("by path");
+}
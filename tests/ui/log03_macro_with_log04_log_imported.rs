@@ -0,0 +1,7 @@
+// With log 0.4's `log!` imported, log 0.3's `info!` expands to a call of the
+// wrong `log!` and hands it a log 0.3 `LogLevel`.
+use log04::log;
+
+fn main() {
+    log03::info!("by path");
+}
@@ -0,0 +1,5 @@
+// Both crates call the type `log::LogLevel`, but `c` is built against log 0.3.8
+// and `b` against log 0.3.9, so they are still two unrelated types.
+fn main() {
+    b::log_at(c::level(), "from c to b");
+}